@@ -0,0 +1,240 @@
+use std::{fmt::Display, io::Cursor};
+
+use image::{
+    imageops::FilterType, DynamicImage, GenericImageView, ImageError, ImageFormat, RgbaImage,
+};
+
+use crate::tts::{CardShape, SheetLayout};
+
+#[derive(Debug)]
+pub enum AtlasError {
+    WrongImageCount {
+        expected: usize,
+        obtained: usize,
+    },
+    NoImages,
+    CouldntDecodeImage {
+        card_name: String,
+        error: ImageError,
+    },
+    CouldntEncodeImage {
+        error: ImageError,
+    },
+}
+
+impl Display for AtlasError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WrongImageCount { expected, obtained } => write!(
+                f,
+                "The sheet has {expected} cards, but {obtained} images were provided"
+            ),
+            Self::NoImages => write!(f, "Tried to composite a sheet with no cards"),
+            Self::CouldntDecodeImage { card_name, error } => {
+                write!(f, "Failed to decode the image for {card_name}: {error}")
+            }
+            Self::CouldntEncodeImage { error } => {
+                write!(f, "Failed to encode the sheet's image: {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AtlasError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::CouldntDecodeImage { error, .. } | Self::CouldntEncodeImage { error } => {
+                Some(error)
+            }
+            Self::WrongImageCount { .. } | Self::NoImages => None,
+        }
+    }
+}
+
+/// Arranges the front images of a sheet's cards into a grid as described by its `SheetLayout`, and encodes it as a PNG.
+///
+/// `fronts` must be in the same order as `layout.cards`. Every cell has the size of the first image, and images of any other size are resized to fit it. Cells left over in the last row are transparent.
+/// # Errors
+/// - If there isn't exactly one image per card in the sheet.
+/// - If the sheet has no cards.
+/// - If the resulting image can't be encoded.
+// Sheets are at most 10 by 7 cards, so positions always fit in a u32
+#[allow(clippy::cast_possible_truncation)]
+pub fn composite_sheet(
+    layout: &SheetLayout,
+    fronts: &[DynamicImage],
+) -> Result<Vec<u8>, AtlasError> {
+    if fronts.len() != layout.cards.len() {
+        return Err(AtlasError::WrongImageCount {
+            expected: layout.cards.len(),
+            obtained: fronts.len(),
+        });
+    }
+    let Some(first) = fronts.first() else {
+        return Err(AtlasError::NoImages);
+    };
+    let (cell_width, cell_height) = (first.width(), first.height());
+    let mut sheet = RgbaImage::new(
+        cell_width * layout.num_width as u32,
+        cell_height * layout.num_height as u32,
+    );
+    for (idx, front) in fronts.iter().enumerate() {
+        let front = if front.width() == cell_width && front.height() == cell_height {
+            front.to_rgba8()
+        } else {
+            front
+                .resize_exact(cell_width, cell_height, FilterType::Triangle)
+                .to_rgba8()
+        };
+        let column = (idx % layout.num_width) as u32;
+        let row = (idx / layout.num_width) as u32;
+        image::imageops::replace(
+            &mut sheet,
+            &front,
+            i64::from(column * cell_width),
+            i64::from(row * cell_height),
+        );
+    }
+    let mut bytes = Cursor::new(vec![]);
+    DynamicImage::ImageRgba8(sheet)
+        .write_to(&mut bytes, ImageFormat::Png)
+        .map_err(|error| AtlasError::CouldntEncodeImage { error })?;
+    Ok(bytes.into_inner())
+}
+
+/// Like `composite_sheet`, but takes the encoded bytes of each front image.
+/// # Errors
+/// - Under the same situations as `composite_sheet`.
+/// - If any of the images can't be decoded.
+pub fn composite_sheet_from_bytes<B: AsRef<[u8]>>(
+    layout: &SheetLayout,
+    fronts: &[B],
+) -> Result<Vec<u8>, AtlasError> {
+    if fronts.len() != layout.cards.len() {
+        return Err(AtlasError::WrongImageCount {
+            expected: layout.cards.len(),
+            obtained: fronts.len(),
+        });
+    }
+    let fronts = fronts
+        .iter()
+        .zip(&layout.cards)
+        .map(|(front, card_name)| {
+            image::load_from_memory(front.as_ref()).map_err(|error| {
+                AtlasError::CouldntDecodeImage {
+                    card_name: card_name.clone(),
+                    error,
+                }
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    composite_sheet(layout, &fronts)
+}
+
+/// Guesses the shape of a card from its image: cards whose four corner pixels are mostly transparent are `CardShape::RoundedRectangle`, and the rest are `CardShape::Rectangle`.
+///
+/// This is only a best-effort heuristic, meant to be called from `GetCardInfo::get_card_shape` implementations that don't know their shape otherwise. Empty images are `CardShape::RoundedRectangle`, the most common shape.
+#[must_use]
+pub fn detect_card_shape(image: &DynamicImage) -> CardShape {
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 {
+        return CardShape::RoundedRectangle;
+    }
+    let corners = [
+        (0, 0),
+        (width - 1, 0),
+        (0, height - 1),
+        (width - 1, height - 1),
+    ];
+    if corners
+        .iter()
+        .all(|(x, y)| image.get_pixel(*x, *y)[3] < u8::MAX / 2)
+    {
+        CardShape::RoundedRectangle
+    } else {
+        CardShape::Rectangle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use super::*;
+
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+    const BLUE: Rgba<u8> = Rgba([0, 0, 255, 255]);
+    const CLEAR: Rgba<u8> = Rgba([0, 0, 0, 0]);
+
+    fn card(width: u32, height: u32, colour: Rgba<u8>) -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_pixel(width, height, colour))
+    }
+
+    fn layout(num_width: usize, num_height: usize, cards: usize) -> SheetLayout {
+        SheetLayout {
+            num_width,
+            num_height,
+            cards: (0..cards).map(|idx| format!("Card {idx}")).collect(),
+            unique_back: false,
+        }
+    }
+
+    #[test]
+    fn transparent_corners_are_rounded() {
+        let mut image = RgbaImage::from_pixel(8, 12, RED);
+        for (x, y) in [(0, 0), (7, 0), (0, 11), (7, 11)] {
+            image.put_pixel(x, y, CLEAR);
+        }
+        let rounded = DynamicImage::ImageRgba8(image.clone());
+        assert_eq!(detect_card_shape(&rounded), CardShape::RoundedRectangle);
+        image.put_pixel(7, 11, RED);
+        let one_square_corner = DynamicImage::ImageRgba8(image);
+        assert_eq!(detect_card_shape(&one_square_corner), CardShape::Rectangle);
+        assert_eq!(detect_card_shape(&card(8, 12, RED)), CardShape::Rectangle);
+        assert_eq!(
+            detect_card_shape(&card(0, 0, RED)),
+            CardShape::RoundedRectangle
+        );
+    }
+
+    #[test]
+    fn cards_are_placed_in_row_major_order() {
+        let fronts = [card(2, 3, RED), card(4, 6, BLUE), card(2, 3, BLUE)];
+        let png = composite_sheet(&layout(2, 2, 3), &fronts).unwrap();
+        let sheet = image::load_from_memory(&png).unwrap().to_rgba8();
+        assert_eq!(sheet.dimensions(), (4, 6));
+        assert_eq!(*sheet.get_pixel(1, 2), RED);
+        assert_eq!(*sheet.get_pixel(3, 2), BLUE);
+        assert_eq!(*sheet.get_pixel(1, 5), BLUE);
+        assert_eq!(*sheet.get_pixel(3, 5), CLEAR);
+    }
+
+    #[test]
+    fn every_card_needs_an_image() {
+        assert!(matches!(
+            composite_sheet(&layout(2, 1, 2), &[card(2, 3, RED)]),
+            Err(AtlasError::WrongImageCount {
+                expected: 2,
+                obtained: 1
+            })
+        ));
+        assert!(matches!(
+            composite_sheet(&layout(0, 0, 0), &[]),
+            Err(AtlasError::NoImages)
+        ));
+    }
+
+    #[test]
+    fn images_are_decoded_from_bytes() {
+        let png = composite_sheet(&layout(1, 1, 1), &[card(2, 3, RED)]).unwrap();
+        let sheet = composite_sheet_from_bytes(&layout(2, 1, 2), &[png.clone(), png]).unwrap();
+        let sheet = image::load_from_memory(&sheet).unwrap().to_rgba8();
+        assert_eq!(sheet.dimensions(), (4, 3));
+        let Err(error) = composite_sheet_from_bytes(&layout(1, 1, 1), &[b"not a png"]) else {
+            panic!("the bytes aren't an image");
+        };
+        assert!(
+            matches!(&error, AtlasError::CouldntDecodeImage { card_name, .. } if card_name == "Card 0")
+        );
+    }
+}
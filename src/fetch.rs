@@ -0,0 +1,193 @@
+use std::time::Duration;
+
+use futures::{stream, StreamExt, TryStreamExt};
+use reqwest::Client;
+
+use crate::{resolve_deck, CardEntry, CardError, GetCardInfo};
+
+/// How many images `download_deck_images` downloads at the same time.
+pub const MAX_CONCURRENT_DOWNLOADS: usize = 8;
+/// How long `download_deck_images` waits for each image before giving up.
+pub const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Downloads the front image of every card in `deck`, returning each card's image URL alongside the image's bytes, in the same order as the deck.
+///
+/// Up to `MAX_CONCURRENT_DOWNLOADS` images are downloaded at the same time, each with a timeout of `DOWNLOAD_TIMEOUT`.
+/// # Errors
+/// Under the same situations as `download_deck_images_with_client`.
+pub async fn download_deck_images<T: GetCardInfo + Clone + Sync>(
+    deck: &[CardEntry<T>],
+) -> Result<Vec<(String, Vec<u8>)>, CardError> {
+    let client = Client::builder()
+        .timeout(DOWNLOAD_TIMEOUT)
+        .build()
+        .map_err(|error| CardError::custom(format!("Couldn't build the HTTP client: {error}")))?;
+    download_deck_images_with_client(&client, deck).await
+}
+
+/// Like `download_deck_images`, but uses `client` for the requests, which allows configuring things like the timeout.
+/// # Errors
+/// - Under any situation that the `GetCardInfo` implementations of the provided type would error.
+/// - `CardError::FrontImageNotFound` if a request fails, times out, or gets a response whose status isn't a success.
+pub async fn download_deck_images_with_client<T: GetCardInfo + Clone + Sync>(
+    client: &Client,
+    deck: &[CardEntry<T>],
+) -> Result<Vec<(String, Vec<u8>)>, CardError> {
+    let mut images = vec![];
+    for entry in resolve_deck(deck.to_vec())? {
+        images.push((
+            entry.card.get_name().to_owned(),
+            entry.card.get_front_image()?,
+        ));
+    }
+    stream::iter(images)
+        .map(|(card_name, image_url)| async move {
+            match download_image(client, &image_url).await {
+                Ok(bytes) => Ok((image_url, bytes)),
+                Err(_) => Err(CardError::FrontImageNotFound {
+                    card_name,
+                    image_url,
+                }),
+            }
+        })
+        .buffered(MAX_CONCURRENT_DOWNLOADS)
+        .try_collect()
+        .await
+}
+
+async fn download_image(client: &Client, url: &str) -> Result<Vec<u8>, reqwest::Error> {
+    let response = client.get(url).send().await?.error_for_status()?;
+    Ok(response.bytes().await?.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::{TcpListener, TcpStream},
+    };
+
+    use super::*;
+    use crate::test_card::ResolvingCard;
+
+    /// A card whose front image is `url`.
+    #[derive(Clone)]
+    struct UrlCard {
+        url: String,
+    }
+
+    impl GetCardInfo for UrlCard {
+        fn get_name(&self) -> &str {
+            &self.url
+        }
+
+        fn get_front_image(&self) -> Result<String, CardError> {
+            Ok(self.url.clone())
+        }
+
+        fn parse(string: &str) -> Result<Self, crate::parser::ParseError> {
+            Ok(Self {
+                url: string.to_owned(),
+            })
+        }
+    }
+
+    /// Starts an HTTP server that answers `/missing.png` with a 404, waits a second before answering `/slow.png`, and answers any other path with the path itself. Returns the server's URL.
+    fn serve() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                std::thread::spawn(move || respond(stream));
+            }
+        });
+        url
+    }
+
+    fn respond(mut stream: TcpStream) {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut request = String::new();
+        reader.read_line(&mut request).unwrap();
+        let mut header = String::new();
+        while reader.read_line(&mut header).unwrap() > 2 {
+            header.clear();
+        }
+        let path = request.split_whitespace().nth(1).unwrap_or("/").to_owned();
+        let (status, body) = match path.as_str() {
+            "/missing.png" => ("404 Not Found", String::new()),
+            "/slow.png" => {
+                std::thread::sleep(Duration::from_secs(1));
+                ("200 OK", path)
+            }
+            _ => ("200 OK", path),
+        };
+        let _ = write!(
+            stream,
+            "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+    }
+
+    fn deck(urls: &[String]) -> Vec<CardEntry<UrlCard>> {
+        urls.iter()
+            .map(|url| CardEntry::try_new(UrlCard::parse(url).unwrap(), 1).unwrap())
+            .collect()
+    }
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn downloads_every_image_in_order() {
+        let server = serve();
+        let urls: Vec<_> = (0..MAX_CONCURRENT_DOWNLOADS * 2)
+            .map(|idx| format!("{server}/{idx}.png"))
+            .collect();
+        let images = block_on(download_deck_images(&deck(&urls))).unwrap();
+        let expected: Vec<_> = (0..MAX_CONCURRENT_DOWNLOADS * 2)
+            .map(|idx| (urls[idx].clone(), format!("/{idx}.png").into_bytes()))
+            .collect();
+        assert_eq!(images, expected);
+    }
+
+    #[test]
+    fn unsuccessful_responses_are_not_found() {
+        let server = serve();
+        let urls = [
+            format!("{server}/found.png"),
+            format!("{server}/missing.png"),
+        ];
+        let error = block_on(download_deck_images(&deck(&urls))).unwrap_err();
+        assert_eq!(
+            error,
+            CardError::FrontImageNotFound {
+                card_name: urls[1].clone(),
+                image_url: urls[1].clone(),
+            }
+        );
+    }
+
+    #[test]
+    fn timeouts_are_not_found() {
+        let server = serve();
+        let client = Client::builder()
+            .timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+        let urls = [format!("{server}/slow.png")];
+        let error = block_on(download_deck_images_with_client(&client, &deck(&urls))).unwrap_err();
+        assert!(matches!(error, CardError::FrontImageNotFound { .. }));
+    }
+
+    #[test]
+    fn download_deck_images_resolves_the_deck() {
+        let error = block_on(download_deck_images(&ResolvingCard::deck(&["Witch"]))).unwrap_err();
+        assert_eq!(ResolvingCard::resolve_calls(), 1);
+        assert!(matches!(error, CardError::FrontImageNotFound { .. }));
+    }
+}
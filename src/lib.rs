@@ -1,13 +1,23 @@
 #![warn(clippy::pedantic)]
 #![warn(clippy::nursery)]
+#[cfg(feature = "image")]
+pub mod atlas;
+#[cfg(feature = "fetch")]
+pub mod fetch;
 // #[cfg(feature = "parser")]
 pub mod parser;
+#[cfg(test)]
+mod test_card;
 pub mod tts;
 
 use serde::{Deserialize, Serialize};
-use std::fmt::Display;
+#[cfg(feature = "fs")]
+use std::path::Path;
+use std::{fmt::Display, io, num::NonZeroU32, path::PathBuf};
 use tts::{CardShape, CustomDeckState};
-use uuid::Uuid;
+
+/// The back image used by `GetCardInfo::get_back_image` when a card type doesn't provide its own. It's the back of Tabletop Simulator's standard playing cards.
+pub const PLACEHOLDER_BACK_IMAGE: &str = "http://cloud-3.steamusercontent.com/ugc/156906385556221451/CE2C3AFE1759790CB0B532FFD636D05A99EC91A4/";
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CardError {
@@ -50,6 +60,8 @@ impl Display for CardError {
     }
 }
 
+impl std::error::Error for CardError {}
+
 impl CardError {
     #[must_use]
     pub const fn custom(message: String) -> Self {
@@ -61,18 +73,46 @@ impl CardError {
 pub trait GetCardInfo: Sized {
     /// The card's name
     fn get_name(&self) -> &str;
+    /// The card's description, shown when hovering over it in Tabletop Simulator
+    fn get_description(&self) -> Option<String> {
+        None
+    }
     /// The card's front image URL
     /// # Errors
     /// Whenever you decide
     fn get_front_image(&self) -> Result<String, CardError>;
-    /// The card's back image URL
+    /// The card's back image URL. Defaults to `PLACEHOLDER_BACK_IMAGE`, so cards that rely on the default all share that back.
     /// # Errors
     /// Whenever you decide
-    fn get_back_image(&self) -> Result<String, CardError>;
-    /// The card shape
+    fn get_back_image(&self) -> Result<String, CardError> {
+        Ok(PLACEHOLDER_BACK_IMAGE.to_owned())
+    }
+    /// The card shape. Defaults to `CardShape::RoundedRectangle`, the shape of most custom cards.
+    /// # Errors
+    /// Whenever you decide
+    fn get_card_shape(&self) -> Result<CardShape, CardError> {
+        Ok(CardShape::RoundedRectangle)
+    }
+    /// Whether the card is in landscape orientation
+    fn is_sideways(&self) -> bool {
+        false
+    }
+    /// How much the card is scaled in Tabletop Simulator, like `1.5` for tarot-sized cards. A deck is scaled like its first card.
+    fn get_scale(&self) -> f64 {
+        1.0
+    }
+    /// Whether the card's back is different from the other cards' backs. When packing cards into sprite sheets, sheets with unique backs need their own back sheet.
+    fn has_unique_back(&self) -> bool {
+        false
+    }
+    /// Prepares every card of a deck at once, before any of their information is asked for. Does nothing by default.
+    ///
+    /// This is meant for card types backed by a remote API, which can fetch every image in a single request and cache it, so that `get_front_image` just returns the cached URL. It's called once per deck by everything in the crate that asks for the information of a deck's cards, like `SaveState::new_with_deck`, `DeckWriter`, `card_objects` or `validate_deck`.
     /// # Errors
     /// Whenever you decide
-    fn get_card_shape(&self) -> Result<CardShape, CardError>;
+    fn resolve_batch(_cards: &mut [Self]) -> Result<(), CardError> {
+        Ok(())
+    }
     /// Turns a String into a card.
     /// # Errors
     /// Whenever you decide
@@ -80,29 +120,826 @@ pub trait GetCardInfo: Sized {
     fn parse(string: &str) -> Result<Self, parser::ParseError>;
 }
 
+/// Like `GetCardInfo`, but for card types that need to do asynchronous work, like network requests, to find out their images and shape.
+#[cfg(feature = "async")]
+pub trait GetCardInfoAsync: Sized {
+    /// The card's name
+    fn get_name(&self) -> &str;
+    /// The card's description, shown when hovering over it in Tabletop Simulator
+    fn get_description(&self) -> Option<String> {
+        None
+    }
+    /// The card's front image URL
+    /// # Errors
+    /// Whenever you decide
+    fn get_front_image(
+        &self,
+    ) -> impl std::future::Future<Output = Result<String, CardError>> + Send;
+    /// The card's back image URL
+    /// # Errors
+    /// Whenever you decide
+    fn get_back_image(&self)
+        -> impl std::future::Future<Output = Result<String, CardError>> + Send;
+    /// The card shape
+    /// # Errors
+    /// Whenever you decide
+    fn get_card_shape(
+        &self,
+    ) -> impl std::future::Future<Output = Result<CardShape, CardError>> + Send;
+    /// Whether the card is in landscape orientation
+    fn is_sideways(&self) -> bool {
+        false
+    }
+    /// How much the card is scaled in Tabletop Simulator, like `1.5` for tarot-sized cards. A deck is scaled like its first card.
+    fn get_scale(&self) -> f64 {
+        1.0
+    }
+    /// Whether the card's back is different from the other cards' backs
+    fn has_unique_back(&self) -> bool {
+        false
+    }
+}
+
 #[derive(Clone)]
-pub struct CardEntry<T: GetCardInfo + Clone> {
+pub struct CardEntry<T> {
     pub card: T,
-    pub amount: i64,
+    pub amount: NonZeroU32,
 }
 
 impl<T: GetCardInfo + Clone> CardEntry<T> {
+    #[must_use]
+    pub const fn new(card: T, amount: NonZeroU32) -> Self {
+        Self { card, amount }
+    }
+
+    /// Like `new`, but takes any integer as the amount.
+    /// # Errors
+    /// If the amount is 0, negative, or doesn't fit in a `u32`.
+    pub fn try_new(card: T, amount: i64) -> Result<Self, CardError> {
+        match u32::try_from(amount).ok().and_then(NonZeroU32::new) {
+            Some(amount) => Ok(Self::new(card, amount)),
+            None => Err(CardError::custom(format!(
+                "Tried to create {} with an amount of {amount}, but it must be between 1 and {}",
+                card.get_name(),
+                u32::MAX
+            ))),
+        }
+    }
+
+    /// Turns the card into another type of card, keeping the amount.
+    #[must_use]
+    pub fn map<U: GetCardInfo + Clone>(self, f: impl FnOnce(T) -> U) -> CardEntry<U> {
+        CardEntry::new(f(self.card), self.amount)
+    }
+
+    /// Like `map`, but the conversion can fail.
+    /// # Errors
+    /// If `f` fails.
+    pub fn try_map<U: GetCardInfo + Clone, E>(
+        self,
+        f: impl FnOnce(T) -> Result<U, E>,
+    ) -> Result<CardEntry<U>, E> {
+        Ok(CardEntry::new(f(self.card)?, self.amount))
+    }
+
     /// # Errors
     /// Whenever any of the `GetCardInfo` implementations in the supplied type error.
     pub fn get_custom_deck_state(&self) -> Result<CustomDeckState, CardError> {
+        self.custom_deck_state_with_back(self.card.get_back_image()?)
+    }
+
+    /// Like `get_custom_deck_state`, but with `back_url` as the back instead of the one from `GetCardInfo::get_back_image`.
+    fn custom_deck_state_with_back(&self, back_url: String) -> Result<CustomDeckState, CardError> {
         Ok(CustomDeckState {
             name: self.card.get_name().to_owned(),
             face_url: self.card.get_front_image()?,
-            back_url: self.card.get_back_image()?,
+            back_url,
             num_width: Some(1),
             num_height: Some(1),
             back_is_hidden: true,
-            unique_back: false,
-            r#type: self.card.get_card_shape()?.into(),
+            unique_back: self.card.has_unique_back(),
+            r#type: self.card.get_card_shape()?,
         })
     }
 }
 
-fn generate_guid() -> String {
-    Uuid::new_v4().to_string()
+/// Lets `GetCardInfo::resolve_batch` prepare every card of a deck at once, keeping the amounts and the order of the cards.
+/// # Errors
+/// If `GetCardInfo::resolve_batch` fails.
+pub fn resolve_deck<T: GetCardInfo + Clone>(
+    deck: Vec<CardEntry<T>>,
+) -> Result<Vec<CardEntry<T>>, CardError> {
+    let (mut cards, amounts): (Vec<T>, Vec<NonZeroU32>) = deck
+        .into_iter()
+        .map(|entry| (entry.card, entry.amount))
+        .unzip();
+    T::resolve_batch(&mut cards)?;
+    Ok(cards
+        .into_iter()
+        .zip(amounts)
+        .map(|(card, amount)| CardEntry::new(card, amount))
+        .collect())
+}
+
+/// Iterates over every card of a deck, repeating each card as many times as its amount.
+pub fn expand_deck<T: GetCardInfo + Clone>(deck: &[CardEntry<T>]) -> impl Iterator<Item = &T> {
+    deck.iter().flat_map(|entry| {
+        let amount = usize::try_from(entry.amount.get()).unwrap_or(usize::MAX);
+        std::iter::repeat_n(&entry.card, amount)
+    })
+}
+
+/// The total amount of cards in a deck. The total saturates at `u64::MAX` instead of overflowing.
+#[must_use]
+pub fn deck_size<T: GetCardInfo + Clone>(deck: &[CardEntry<T>]) -> u64 {
+    deck.iter().fold(0, |size: u64, entry| {
+        size.saturating_add(u64::from(entry.amount.get()))
+    })
+}
+
+/// The amount of distinct cards in a deck.
+#[must_use]
+pub const fn distinct_count<T: GetCardInfo + Clone>(deck: &[CardEntry<T>]) -> usize {
+    deck.len()
+}
+
+/// What `sort_deck` orders cards by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortKey {
+    /// Alphabetically, ignoring casing.
+    Name,
+    /// From the highest amount to the lowest.
+    AmountDesc,
+}
+
+/// Sorts a deck by `by`. The sort is stable, so cards that compare as equal keep their order. Since each card's id depends on its place in the deck, sorting a deck before building a save makes the ids reproducible.
+pub fn sort_deck<T: GetCardInfo + Clone>(deck: &mut [CardEntry<T>], by: SortKey) {
+    match by {
+        SortKey::Name => deck.sort_by_cached_key(|entry| entry.card.get_name().to_lowercase()),
+        SortKey::AmountDesc => deck.sort_by_key(|entry| std::cmp::Reverse(entry.amount)),
+    }
+}
+
+/// A difference between two versions of a deck, as found by `deck_diff`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeckChange {
+    Added { name: String, amount: u32 },
+    Removed { name: String, amount: u32 },
+    AmountChanged { name: String, from: u32, to: u32 },
+}
+
+impl Display for DeckChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Added { name, amount } => write!(f, "+{amount} {name} (new card)"),
+            Self::Removed { name, amount } => write!(f, "-{amount} {name} (removed card)"),
+            Self::AmountChanged { name, from, to } if to >= from => {
+                write!(f, "+{} {name}", to - from)
+            }
+            Self::AmountChanged { name, from, to } => write!(f, "-{} {name}", from - to),
+        }
+    }
+}
+
+/// The changes that turn `old` into `new`. Cards are matched by their name, and entries with the same name are counted together. Cards that were added or changed come first, in the order they appear in `new`, followed by the removed cards, in the order they appear in `old`.
+#[must_use]
+pub fn deck_diff<T: GetCardInfo + Clone>(
+    old: &[CardEntry<T>],
+    new: &[CardEntry<T>],
+) -> Vec<DeckChange> {
+    let old = amounts_by_name(old);
+    let new = amounts_by_name(new);
+    let mut changes = vec![];
+    for &(name, to) in &new {
+        match old.iter().find(|(old_name, _)| *old_name == name) {
+            None => changes.push(DeckChange::Added {
+                name: name.to_owned(),
+                amount: to,
+            }),
+            Some(&(_, from)) if from != to => changes.push(DeckChange::AmountChanged {
+                name: name.to_owned(),
+                from,
+                to,
+            }),
+            Some(_) => {}
+        }
+    }
+    for &(name, amount) in &old {
+        if !new.iter().any(|(new_name, _)| *new_name == name) {
+            changes.push(DeckChange::Removed {
+                name: name.to_owned(),
+                amount,
+            });
+        }
+    }
+    changes
+}
+
+/// The total amount of each card name in a deck, in the order the names first appear.
+fn amounts_by_name<T: GetCardInfo + Clone>(deck: &[CardEntry<T>]) -> Vec<(&str, u32)> {
+    let mut amounts: Vec<(&str, u32)> = vec![];
+    for entry in deck {
+        let name = entry.card.get_name();
+        match amounts.iter_mut().find(|(used, _)| *used == name) {
+            Some((_, amount)) => *amount = amount.saturating_add(entry.amount.get()),
+            None => amounts.push((name, entry.amount.get())),
+        }
+    }
+    amounts
+}
+
+/// Checks that the images of every card in a deck exist, for the images that are local files. Image URLs starting with `file://` and URLs without a scheme are treated as local paths, and any other URL, like an `http` or `https` one, is skipped. Returns an error for each missing image, along with any errors from the `GetCardInfo` implementations. If `GetCardInfo::resolve_batch` fails, its error is the only one returned.
+#[cfg(feature = "fs")]
+#[must_use]
+pub fn validate_deck<T: GetCardInfo + Clone>(deck: &[CardEntry<T>]) -> Vec<CardError> {
+    let deck = match resolve_deck(deck.to_vec()) {
+        Ok(deck) => deck,
+        Err(error) => return vec![error],
+    };
+    let mut errors = vec![];
+    for entry in &deck {
+        let card_name = entry.card.get_name();
+        match entry.card.get_front_image() {
+            Ok(image_url) if is_missing_local_file(&image_url) => {
+                errors.push(CardError::FrontImageNotFound {
+                    card_name: card_name.to_owned(),
+                    image_url,
+                });
+            }
+            Ok(_) => {}
+            Err(error) => errors.push(error),
+        }
+        match entry.card.get_back_image() {
+            Ok(image_url) if is_missing_local_file(&image_url) => {
+                errors.push(CardError::BackImageFileError {
+                    card_name: card_name.to_owned(),
+                    image_url,
+                });
+            }
+            Ok(_) => {}
+            Err(error) => errors.push(error),
+        }
+    }
+    errors
+}
+
+/// Whether `image_url` refers to a local file that doesn't exist.
+#[cfg(feature = "fs")]
+fn is_missing_local_file(image_url: &str) -> bool {
+    let path = match image_url.strip_prefix("file://") {
+        Some(path) => path,
+        None if image_url.contains("://") => return false,
+        None => image_url,
+    };
+    !Path::new(path).exists()
+}
+
+/// Any error this crate can produce, so that applications can use `?` on all of them. The specific error is kept as the source.
+#[derive(Debug)]
+pub enum Error {
+    Card { error: CardError },
+    Parse { errors: Vec<parser::ParseError> },
+    Save { error: tts::SaveError },
+    Json { error: serde_json::Error },
+    Io { path: PathBuf, error: io::Error },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Card { error } => write!(f, "{error}"),
+            Self::Parse { errors } => {
+                let mut errors = errors.iter();
+                if let Some(error) = errors.next() {
+                    write!(f, "{error}")?;
+                }
+                for error in errors {
+                    write!(f, "\n{error}")?;
+                }
+                Ok(())
+            }
+            Self::Save { error } => write!(f, "{error}"),
+            Self::Json { error } => write!(f, "{error}"),
+            Self::Io { path, error } => write!(f, "{}: {error}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Card { error } => Some(error),
+            Self::Parse { errors } => errors
+                .first()
+                .map(|error| error as &(dyn std::error::Error + 'static)),
+            Self::Save { error } => Some(error),
+            Self::Json { error } => Some(error),
+            Self::Io { error, .. } => Some(error),
+        }
+    }
+}
+
+impl From<CardError> for Error {
+    fn from(error: CardError) -> Self {
+        Self::Card { error }
+    }
+}
+
+impl From<parser::ParseError> for Error {
+    fn from(error: parser::ParseError) -> Self {
+        Self::Parse {
+            errors: vec![error],
+        }
+    }
+}
+
+impl From<Vec<parser::ParseError>> for Error {
+    fn from(errors: Vec<parser::ParseError>) -> Self {
+        Self::Parse { errors }
+    }
+}
+
+impl From<parser::Error> for Error {
+    fn from(error: parser::Error) -> Self {
+        parser::ParseError::from(error).into()
+    }
+}
+
+impl From<tts::SaveError> for Error {
+    fn from(error: tts::SaveError) -> Self {
+        Self::Save { error }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json { error }
+    }
+}
+
+#[cfg(feature = "fs")]
+impl From<BuildError> for Error {
+    fn from(error: BuildError) -> Self {
+        match error {
+            BuildError::CouldntParseDecklist { errors } => errors.into(),
+            BuildError::CouldntBuildDeck { error } => error.into(),
+            BuildError::CouldntSerialize { error } => error.into(),
+            BuildError::CouldntWrite { error } => error.into(),
+            BuildError::CouldntReadDirectory { path, error } => Self::Io { path, error },
+        }
+    }
+}
+
+/// Everything that can go wrong in `build_deck_to_tts_dir` and `build_all`.
+#[cfg(feature = "fs")]
+#[derive(Debug)]
+pub enum BuildError {
+    CouldntParseDecklist { errors: Vec<parser::ParseError> },
+    CouldntBuildDeck { error: CardError },
+    CouldntSerialize { error: serde_json::Error },
+    CouldntWrite { error: tts::SaveError },
+    CouldntReadDirectory { path: PathBuf, error: io::Error },
+}
+
+#[cfg(feature = "fs")]
+impl Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CouldntParseDecklist { errors } => {
+                write!(f, "Couldn't parse the decklist:")?;
+                for error in errors {
+                    write!(f, "\n{error}")?;
+                }
+                Ok(())
+            }
+            Self::CouldntBuildDeck { error } => write!(f, "Couldn't build the deck: {error}"),
+            Self::CouldntSerialize { error } => {
+                write!(f, "Couldn't serialize the save: {error}")
+            }
+            Self::CouldntWrite { error } => write!(f, "Couldn't write the save: {error}"),
+            Self::CouldntReadDirectory { path, error } => write!(
+                f,
+                "Couldn't read the directory {} with error: {error}",
+                path.display()
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "fs")]
+impl std::error::Error for BuildError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::CouldntParseDecklist { errors } => errors
+                .first()
+                .map(|error| error as &(dyn std::error::Error + 'static)),
+            Self::CouldntBuildDeck { error } => Some(error),
+            Self::CouldntSerialize { error } => Some(error),
+            Self::CouldntWrite { error } => Some(error),
+            Self::CouldntReadDirectory { error, .. } => Some(error),
+        }
+    }
+}
+
+#[cfg(feature = "fs")]
+impl From<Vec<parser::ParseError>> for BuildError {
+    fn from(errors: Vec<parser::ParseError>) -> Self {
+        Self::CouldntParseDecklist { errors }
+    }
+}
+
+#[cfg(feature = "fs")]
+impl From<CardError> for BuildError {
+    fn from(error: CardError) -> Self {
+        Self::CouldntBuildDeck { error }
+    }
+}
+
+#[cfg(feature = "fs")]
+impl From<serde_json::Error> for BuildError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::CouldntSerialize { error }
+    }
+}
+
+#[cfg(feature = "fs")]
+impl From<tts::SaveError> for BuildError {
+    fn from(error: tts::SaveError) -> Self {
+        Self::CouldntWrite { error }
+    }
+}
+
+/// Parses the decklist at `decklist_path`, builds a deck out of it and writes it to Tabletop Simulator's saved objects directory as `output_name`.
+///
+/// `image` is written alongside it as the object's thumbnail, if provided.
+/// # Errors
+/// - If the decklist can't be parsed, as in `parser::parse_file`
+/// - Under any situation that the `GetCardInfo` implementations of the provided type would error
+/// - If the save can't be written, as in `tts::write_to_tts_dir`
+#[cfg(feature = "fs")]
+pub fn build_deck_to_tts_dir<T: GetCardInfo + Clone>(
+    decklist_path: &PathBuf,
+    output_name: impl AsRef<Path>,
+    image: Option<&[u8]>,
+) -> Result<(), BuildError> {
+    let json = build_deck::<T>(decklist_path)?.to_json()?;
+    match image {
+        Some(image) => tts::write_to_tts_dir(output_name, json, image)?,
+        None => tts::write_json_to_tts_dir(output_name, json)?,
+    }
+    Ok(())
+}
+
+/// Parses and builds every `.txt` decklist in `dir`, returning the save of each one, or why it couldn't be built, by the path of its decklist.
+///
+/// A decklist that fails doesn't stop the rest from being built. Files are built in the order of their paths, and subdirectories aren't searched. If `dir` can't be read, the result only has the error for `dir`.
+#[cfg(feature = "fs")]
+#[must_use]
+pub fn build_all<T: GetCardInfo + Clone>(
+    dir: &Path,
+) -> Vec<(PathBuf, Result<tts::SaveState, BuildError>)> {
+    let read_dir_error = |error| BuildError::CouldntReadDirectory {
+        path: dir.to_path_buf(),
+        error,
+    };
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) => return vec![(dir.to_path_buf(), Err(read_dir_error(error)))],
+    };
+    let mut paths = vec![];
+    for entry in entries {
+        match entry {
+            Ok(entry) => paths.push(entry.path()),
+            Err(error) => return vec![(dir.to_path_buf(), Err(read_dir_error(error)))],
+        }
+    }
+    paths.retain(|path| {
+        path.is_file()
+            && path
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("txt"))
+    });
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| {
+            let save = build_deck::<T>(&path);
+            (path, save)
+        })
+        .collect()
+}
+
+/// Parses the decklist at `path` and builds a save with its deck.
+#[cfg(feature = "fs")]
+fn build_deck<T: GetCardInfo + Clone>(path: &PathBuf) -> Result<tts::SaveState, BuildError> {
+    let deck = parser::parse_file::<T>(path)?;
+    Ok(tts::SaveState::new_with_deck(deck)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error as _;
+
+    use super::*;
+    use crate::test_card::{deck, ResolvingCard, TestCard};
+
+    /// A card that only implements the methods `GetCardInfo` requires.
+    #[derive(Clone)]
+    struct MinimalCard;
+
+    impl GetCardInfo for MinimalCard {
+        fn get_name(&self) -> &'static str {
+            "Minimal"
+        }
+
+        fn get_front_image(&self) -> Result<String, CardError> {
+            Ok("https://example.com/minimal.png".to_owned())
+        }
+
+        fn parse(_string: &str) -> Result<Self, parser::ParseError> {
+            Ok(Self)
+        }
+    }
+
+    /// A card whose front image is given when it's made, like a local file.
+    #[derive(Clone)]
+    struct FileCard {
+        name: String,
+        front_image: String,
+    }
+
+    impl GetCardInfo for FileCard {
+        fn get_name(&self) -> &str {
+            &self.name
+        }
+
+        fn get_front_image(&self) -> Result<String, CardError> {
+            Ok(self.front_image.clone())
+        }
+
+        fn parse(string: &str) -> Result<Self, parser::ParseError> {
+            Ok(Self {
+                name: string.to_owned(),
+                front_image: string.to_owned(),
+            })
+        }
+    }
+
+    fn names<T: GetCardInfo + Clone>(deck: &[CardEntry<T>]) -> Vec<&str> {
+        deck.iter().map(|entry| entry.card.get_name()).collect()
+    }
+
+    #[test]
+    fn default_methods() {
+        let card = MinimalCard;
+        assert_eq!(card.get_card_shape(), Ok(CardShape::RoundedRectangle));
+        assert_eq!(card.get_back_image(), Ok(PLACEHOLDER_BACK_IMAGE.to_owned()));
+        assert_eq!(card.get_description(), None);
+        assert!(!card.is_sideways());
+        assert!(!card.has_unique_back());
+        let state = CardEntry::new(card, NonZeroU32::MIN)
+            .get_custom_deck_state()
+            .unwrap();
+        assert_eq!(state.back_url, PLACEHOLDER_BACK_IMAGE);
+        assert_eq!(state.r#type, CardShape::RoundedRectangle);
+    }
+
+    #[test]
+    fn try_new_rejects_amounts_that_dont_fit() {
+        for amount in [0, -1, i64::MIN, i64::from(u32::MAX) + 1, i64::MAX] {
+            assert!(CardEntry::try_new(TestCard::new("Island"), amount).is_err());
+        }
+        let entry = CardEntry::try_new(TestCard::new("Island"), 4).unwrap();
+        assert_eq!(entry.amount.get(), 4);
+        assert_eq!(entry.card.get_name(), "Island");
+        let entry = CardEntry::try_new(TestCard::new("Island"), i64::from(u32::MAX)).unwrap();
+        assert_eq!(entry.amount.get(), u32::MAX);
+    }
+
+    #[test]
+    fn expand_deck_repeats_each_card() {
+        let deck = deck(&[("A", 2), ("B", 1)]);
+        let cards: Vec<_> = expand_deck(&deck).map(TestCard::get_name).collect();
+        assert_eq!(cards, vec!["A", "A", "B"]);
+    }
+
+    #[test]
+    fn deck_size_doesnt_overflow() {
+        assert_eq!(deck_size(&deck(&[("A", 2), ("B", 1)])), 3);
+        assert_eq!(deck_size::<TestCard>(&[]), 0);
+        let huge = deck(&[("A", u32::MAX), ("B", u32::MAX), ("C", u32::MAX)]);
+        assert_eq!(deck_size(&huge), 3 * u64::from(u32::MAX));
+        assert_eq!(distinct_count(&huge), 3);
+    }
+
+    #[test]
+    fn sort_deck_by_name_ignores_casing() {
+        let mut cards = deck(&[("Zebra", 1), ("apple", 2), ("Apple", 3)]);
+        sort_deck(&mut cards, SortKey::Name);
+        assert_eq!(names(&cards), vec!["apple", "Apple", "Zebra"]);
+        sort_deck(&mut cards, SortKey::AmountDesc);
+        assert_eq!(names(&cards), vec!["Apple", "apple", "Zebra"]);
+    }
+
+    #[test]
+    fn deck_diff_matches_cards_by_name() {
+        let old = deck(&[("Island", 2), ("Bolt", 4), ("Forest", 1)]);
+        let new = deck(&[("Island", 3), ("Bolt", 4), ("Swamp", 1)]);
+        let changes = deck_diff(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                DeckChange::AmountChanged {
+                    name: "Island".to_owned(),
+                    from: 2,
+                    to: 3
+                },
+                DeckChange::Added {
+                    name: "Swamp".to_owned(),
+                    amount: 1
+                },
+                DeckChange::Removed {
+                    name: "Forest".to_owned(),
+                    amount: 1
+                },
+            ]
+        );
+        let lines: Vec<_> = changes.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "+1 Island",
+                "+1 Swamp (new card)",
+                "-1 Forest (removed card)"
+            ]
+        );
+        assert!(deck_diff(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn map_keeps_the_amount() {
+        let raw = deck(&[("Island", 2), ("Bolt", 4)]);
+        let enriched: Vec<_> = raw
+            .into_iter()
+            .map(|entry| {
+                entry.map(|card| FileCard {
+                    name: card.get_name().to_owned(),
+                    front_image: card.get_front_image().unwrap(),
+                })
+            })
+            .collect();
+        assert_eq!(names(&enriched), vec!["Island", "Bolt"]);
+        assert_eq!(enriched[1].amount.get(), 4);
+        assert_eq!(
+            enriched[0].card.front_image,
+            "https://example.com/Island.png"
+        );
+        let Err(error) = CardEntry::new(TestCard::new("Island"), NonZeroU32::MIN).try_map(|card| {
+            Err::<FileCard, _>(CardError::custom(format!(
+                "{} isn't in the database",
+                card.get_name()
+            )))
+        }) else {
+            panic!("the conversion fails");
+        };
+        assert_eq!(error.to_string(), "Island isn't in the database");
+    }
+
+    #[test]
+    fn resolve_deck_keeps_the_amounts_and_the_order() {
+        let deck = resolve_deck(ResolvingCard::deck(&["Witch", "Mechanic"])).unwrap();
+        assert_eq!(ResolvingCard::resolve_calls(), 1);
+        assert_eq!(names(&deck), vec!["Witch", "Mechanic"]);
+        assert!(deck.iter().all(|entry| entry.amount.get() == 2));
+        assert!(deck
+            .iter()
+            .all(|entry| entry.card.get_front_image().is_ok()));
+    }
+
+    #[test]
+    fn errors_keep_their_source() {
+        let error = Error::from(CardError::custom("Not a card".to_owned()));
+        assert!(error.source().is_some());
+        assert_eq!(error.to_string(), "Not a card");
+        let error = Error::from(parser::Error::NameIsEmpty);
+        assert!(error.source().is_some());
+        assert!(Error::from(Vec::<parser::ParseError>::new())
+            .source()
+            .is_none());
+        let error = Error::from(serde_json::from_str::<u8>("x").unwrap_err());
+        assert!(error.source().is_some());
+        let error = Error::from(tts::SaveError::CouldntFindSaveDirectory);
+        assert!(error.source().is_some());
+        let error = Error::Io {
+            path: PathBuf::from("decks"),
+            error: io::Error::new(io::ErrorKind::NotFound, "missing"),
+        };
+        assert!(error.source().is_some());
+        assert_eq!(error.to_string(), "decks: missing");
+    }
+
+    #[cfg(feature = "fs")]
+    mod files {
+        use super::*;
+        use crate::test_card::temp_dir;
+
+        #[test]
+        fn validate_deck_finds_missing_local_images() {
+            let dir = temp_dir("validate_deck");
+            let found = dir.join("found.png");
+            std::fs::write(&found, b"").unwrap();
+            let missing = dir.join("missing.png");
+            let card = |name: &str, front_image: String| {
+                CardEntry::new(
+                    FileCard {
+                        name: name.to_owned(),
+                        front_image,
+                    },
+                    NonZeroU32::MIN,
+                )
+            };
+            let deck = vec![
+                card("Found", found.display().to_string()),
+                card("Also Found", format!("file://{}", found.display())),
+                card("Remote", "https://example.com/remote.png".to_owned()),
+                card("Lost", missing.display().to_string()),
+            ];
+            assert_eq!(
+                validate_deck(&deck),
+                vec![CardError::FrontImageNotFound {
+                    card_name: "Lost".to_owned(),
+                    image_url: missing.display().to_string(),
+                }]
+            );
+        }
+
+        #[test]
+        fn build_deck_to_tts_dir_reports_parse_errors() {
+            let dir = temp_dir("build_deck_to_tts_dir_errors");
+            let decklist = dir.join("deck.txt");
+            std::fs::write(&decklist, "four Island\n").unwrap();
+            let Err(error) = build_deck_to_tts_dir::<TestCard>(&decklist, "deck", None) else {
+                panic!("four isn't an amount");
+            };
+            assert!(matches!(error, BuildError::CouldntParseDecklist { .. }));
+            assert!(std::error::Error::source(&error).is_some());
+        }
+
+        #[test]
+        fn build_all_keeps_going_after_a_bad_decklist() {
+            let dir = temp_dir("build_all");
+            std::fs::write(dir.join("good.txt"), "2 Island\n1 Bolt\n").unwrap();
+            std::fs::write(dir.join("bad.txt"), "four Island\n").unwrap();
+            std::fs::write(dir.join("notes.md"), "4 Island\n").unwrap();
+            let results = build_all::<TestCard>(&dir);
+            assert_eq!(results.len(), 2);
+            assert_eq!(results[0].0, dir.join("bad.txt"));
+            assert!(matches!(
+                results[0].1,
+                Err(BuildError::CouldntParseDecklist { .. })
+            ));
+            assert_eq!(results[1].0, dir.join("good.txt"));
+            let save = results[1].1.as_ref().unwrap();
+            assert_eq!(save.deck_face_urls().unwrap().len(), 2);
+        }
+
+        #[test]
+        fn build_all_reports_unreadable_directories() {
+            let dir = temp_dir("build_all_missing").join("missing");
+            let results = build_all::<TestCard>(&dir);
+            assert_eq!(results.len(), 1);
+            assert!(matches!(
+                results[0].1,
+                Err(BuildError::CouldntReadDirectory { .. })
+            ));
+            assert!(results[0].1.as_ref().unwrap_err().source().is_some());
+        }
+
+        #[cfg(target_os = "linux")]
+        #[test]
+        fn build_deck_to_tts_dir_writes_the_save() {
+            use crate::test_card::with_temp_home;
+
+            let dir = temp_dir("build_deck_to_tts_dir");
+            let decklist = dir.join("deck.txt");
+            std::fs::write(&decklist, "2 Island\n1 Bolt\n").unwrap();
+            with_temp_home("build_deck_to_tts_dir_home", |home| {
+                build_deck_to_tts_dir::<TestCard>(&decklist, "deck", Some(b"png")).unwrap();
+                let saved_objects = tts::saved_objects_dir_from_home(home);
+                let json = std::fs::read_to_string(saved_objects.join("deck.json")).unwrap();
+                let save = tts::SaveState::from_json(&json).unwrap();
+                assert_eq!(save.deck_face_urls().unwrap().len(), 2);
+                assert_eq!(
+                    std::fs::read(saved_objects.join("deck.png")).unwrap(),
+                    b"png"
+                );
+            });
+        }
+
+        #[test]
+        fn validate_deck_resolves_the_deck() {
+            assert!(validate_deck(&ResolvingCard::deck(&["Witch"])).is_empty());
+            assert_eq!(ResolvingCard::resolve_calls(), 1);
+        }
+    }
 }
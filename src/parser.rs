@@ -1,13 +1,30 @@
+#[cfg(feature = "cockatrice")]
+pub mod cockatrice;
+#[cfg(feature = "csv")]
+pub mod csv;
+pub mod forge;
+pub mod mtga;
+pub mod sections;
+
 use std::{
+    collections::HashMap,
     fmt::Display,
-    fs::File,
-    io::{self, BufRead, BufReader},
-    num::ParseIntError,
+    io::{self, BufRead},
+    num::NonZeroU32,
     path::PathBuf,
+    str::FromStr,
+    sync::Arc,
 };
 
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "fs")]
+use std::{fs::File, io::BufReader};
+
 use crate::{CardEntry, GetCardInfo};
 
+/// Parsing errors. `io::Error`s are kept behind an `Arc` so that errors can be cloned. When serialized, the wrapped errors are stored as their messages.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(from = "ErrorRepr", into = "ErrorRepr")]
 pub enum Error {
     UnexpectedChar {
         obtained: char,
@@ -16,22 +33,35 @@ pub enum Error {
     AmountIsZero {
         card_name: String,
     },
+    AmountIsNegative {
+        card_name: String,
+        amount: i64,
+    },
+    AmountIsTooLarge {
+        card_name: String,
+        amount: i64,
+    },
     NameIsEmpty,
     NotANumber {
         string: String,
-        error: ParseIntError,
+        /// Why `string` isn't a number, which is a `ParseIntError` unless the error was deserialized, in which case it only has its message.
+        error: Arc<dyn std::error::Error + Send + Sync>,
     },
     CantOpenFile {
         path: PathBuf,
-        error: io::Error,
+        error: Arc<io::Error>,
     },
     NameMultipleTimes {
         name: String,
+        original: String,
     },
     CouldntReadLine {
-        path: PathBuf,
+        path: Option<PathBuf>,
         line: usize,
-        error: io::Error,
+        error: Arc<io::Error>,
+    },
+    Custom {
+        message: String,
     },
 }
 
@@ -61,6 +91,15 @@ impl Display for Error {
                 f,
                 "Tried to create {card_name} with an amount of 0, which is frankly ridiculous"
             ),
+            Self::AmountIsNegative { card_name, amount } => write!(
+                f,
+                "Tried to create {card_name} with a negative amount ({amount})"
+            ),
+            Self::AmountIsTooLarge { card_name, amount } => write!(
+                f,
+                "Tried to create {card_name} with an amount of {amount}, but it can be at most {}",
+                u32::MAX
+            ),
             Self::NameIsEmpty => write!(f, "Tried to create a card with an empty name"),
             Self::NotANumber { string, error } => {
                 write!(f, "Failed to parse `{string}` as a number:\n  {error}")
@@ -70,27 +109,208 @@ impl Display for Error {
                 "Failed to load file `{}`, with the following error: {error}",
                 path.display()
             ),
-            Self::NameMultipleTimes { name } => write!(
+            Self::NameMultipleTimes { name, original } if name == original => write!(
                 f,
                 "The name `{name}` appears multiple times, which is not allowed."
             ),
-            Self::CouldntReadLine { path, line, error } => {
+            Self::NameMultipleTimes { name, original } => write!(
+                f,
+                "The name `{name}` appears multiple times (first as `{original}`), which is not allowed."
+            ),
+            Self::CouldntReadLine {
+                path: Some(path),
+                line,
+                error,
+            } => {
                 write!(
                     f,
                     "Failed to read line {line} in file {}:\n  {error}",
                     path.display()
                 )
             }
+            Self::CouldntReadLine {
+                path: None,
+                line,
+                error,
+            } => write!(f, "Failed to read line {line}:\n  {error}"),
+            Self::Custom { message } => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::NotANumber { error, .. } => Some(error.as_ref()),
+            Self::CantOpenFile { error, .. } | Self::CouldntReadLine { error, .. } => {
+                Some(error.as_ref())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Serializable mirror of `Error`, with the errors it wraps replaced by their messages.
+#[derive(Serialize, Deserialize)]
+enum ErrorRepr {
+    UnexpectedChar {
+        obtained: char,
+        expected: Vec<String>,
+    },
+    AmountIsZero {
+        card_name: String,
+    },
+    AmountIsNegative {
+        card_name: String,
+        amount: i64,
+    },
+    AmountIsTooLarge {
+        card_name: String,
+        amount: i64,
+    },
+    NameIsEmpty,
+    NotANumber {
+        string: String,
+        error: String,
+    },
+    CantOpenFile {
+        path: PathBuf,
+        error: String,
+    },
+    NameMultipleTimes {
+        name: String,
+        original: String,
+    },
+    CouldntReadLine {
+        path: Option<PathBuf>,
+        line: usize,
+        error: String,
+    },
+    Custom {
+        message: String,
+    },
+}
+
+impl From<Error> for ErrorRepr {
+    fn from(value: Error) -> Self {
+        match value {
+            Error::UnexpectedChar { obtained, expected } => {
+                Self::UnexpectedChar { obtained, expected }
+            }
+            Error::AmountIsZero { card_name } => Self::AmountIsZero { card_name },
+            Error::AmountIsNegative { card_name, amount } => {
+                Self::AmountIsNegative { card_name, amount }
+            }
+            Error::AmountIsTooLarge { card_name, amount } => {
+                Self::AmountIsTooLarge { card_name, amount }
+            }
+            Error::NameIsEmpty => Self::NameIsEmpty,
+            Error::NotANumber { string, error } => Self::NotANumber {
+                string,
+                error: error.to_string(),
+            },
+            Error::CantOpenFile { path, error } => Self::CantOpenFile {
+                path,
+                error: error.to_string(),
+            },
+            Error::NameMultipleTimes { name, original } => {
+                Self::NameMultipleTimes { name, original }
+            }
+            Error::CouldntReadLine { path, line, error } => Self::CouldntReadLine {
+                path,
+                line,
+                error: error.to_string(),
+            },
+            Error::Custom { message } => Self::Custom { message },
         }
     }
 }
 
+impl From<ErrorRepr> for Error {
+    fn from(value: ErrorRepr) -> Self {
+        match value {
+            ErrorRepr::UnexpectedChar { obtained, expected } => {
+                Self::UnexpectedChar { obtained, expected }
+            }
+            ErrorRepr::AmountIsZero { card_name } => Self::AmountIsZero { card_name },
+            ErrorRepr::AmountIsNegative { card_name, amount } => {
+                Self::AmountIsNegative { card_name, amount }
+            }
+            ErrorRepr::AmountIsTooLarge { card_name, amount } => {
+                Self::AmountIsTooLarge { card_name, amount }
+            }
+            ErrorRepr::NameIsEmpty => Self::NameIsEmpty,
+            ErrorRepr::NotANumber { string, error } => Self::NotANumber {
+                string,
+                error: Arc::from(Box::<dyn std::error::Error + Send + Sync>::from(error)),
+            },
+            ErrorRepr::CantOpenFile { path, error } => Self::CantOpenFile {
+                path,
+                error: Arc::new(io::Error::other(error)),
+            },
+            ErrorRepr::NameMultipleTimes { name, original } => {
+                Self::NameMultipleTimes { name, original }
+            }
+            ErrorRepr::CouldntReadLine { path, line, error } => Self::CouldntReadLine {
+                path,
+                line,
+                error: Arc::new(io::Error::other(error)),
+            },
+            ErrorRepr::Custom { message } => Self::Custom { message },
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ParseError {
     position: LinePosition,
     error: Error,
+    #[serde(default)]
+    source_line: Option<String>,
 }
 
 impl ParseError {
+    /// An error with a custom message and an unknown position, for `GetCardInfo::parse` implementations to report their own errors.
+    #[must_use]
+    pub const fn custom(message: String) -> Self {
+        Self {
+            position: LinePosition::void(),
+            error: Error::Custom { message },
+            source_line: None,
+        }
+    }
+
+    /// The line the error happened at, starting from 1, if known.
+    #[must_use]
+    pub const fn line(&self) -> Option<usize> {
+        self.position.line
+    }
+
+    /// The column the error happened at, starting from 1, if known.
+    #[must_use]
+    pub const fn column(&self) -> Option<usize> {
+        self.position.column
+    }
+
+    /// What went wrong.
+    #[must_use]
+    pub const fn error(&self) -> &Error {
+        &self.error
+    }
+
+    /// The text of the line the error happened at, if known.
+    #[must_use]
+    pub fn source_line(&self) -> Option<&str> {
+        self.source_line.as_deref()
+    }
+
+    fn with_source_line(self, source_line: &str) -> Self {
+        Self {
+            source_line: Some(source_line.trim_end_matches(['\n', '\r']).to_owned()),
+            ..self
+        }
+    }
+
     fn at_line(self, line: usize) -> Self {
         Self {
             position: LinePosition {
@@ -104,78 +324,347 @@ impl ParseError {
 
 impl Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.position {
-            LinePosition {
+        self.position.describe("Error", f)?;
+        match &self.source_line {
+            Some(source_line) => write!(f, ": \"{source_line}\"\n  {}", self.error),
+            None => write!(f, ": {}", self.error),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<Error> for ParseError {
+    /// An error at an unknown position.
+    fn from(error: Error) -> Self {
+        Self {
+            position: LinePosition::void(),
+            error,
+            source_line: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct LinePosition {
+    line: Option<usize>,
+    column: Option<usize>,
+}
+
+impl LinePosition {
+    const fn void() -> Self {
+        Self {
+            line: None,
+            column: None,
+        }
+    }
+
+    /// Writes where `what`, like an error, happened.
+    fn describe(self, what: &str, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self {
                 line: None,
                 column: None,
-            } => write!(f, "Error at unknown position: {}", self.error),
-            LinePosition {
+            } => write!(f, "{what} at unknown position"),
+            Self {
                 line: Some(line),
                 column: Some(column),
-            } => {
-                write!(
-                    f,
-                    "Error at line {}, column {}: {}",
-                    line, column, self.error
-                )
-            }
-            LinePosition {
+            } => write!(f, "{what} at line {line}, column {column}"),
+            Self {
                 line: None,
                 column: Some(column),
-            } => {
-                write!(
-                    f,
-                    "Error at unknown line, column {}: {}",
-                    column, self.error
-                )
-            }
-            LinePosition {
+            } => write!(f, "{what} at unknown line, column {column}"),
+            Self {
                 line: Some(line),
                 column: None,
-            } => {
-                write!(f, "Error at line {}: {}", line, self.error)
+            } => write!(f, "{what} at line {line}"),
+        }
+    }
+}
+
+/// Oddities in a decklist that don't stop it from being parsed, but might be mistakes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Warning {
+    /// The amount is above `ParseOptions::high_amount`.
+    HighAmount { card_name: String, amount: u32 },
+    /// An amount of 1 is written as `1x` or `x1`, when it could be left out or written as `1`.
+    RedundantCount { card_name: String },
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::HighAmount { card_name, amount } => write!(
+                f,
+                "{card_name} has an amount of {amount}, which is suspiciously high"
+            ),
+            Self::RedundantCount { card_name } => {
+                write!(f, "{card_name} has its amount of 1 written out with an `x`")
             }
         }
     }
 }
 
-pub struct LinePosition {
-    line: Option<usize>,
-    column: Option<usize>,
+/// A `Warning` along with where it was found.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ParseWarning {
+    position: LinePosition,
+    warning: Warning,
 }
 
-impl LinePosition {
-    const fn void() -> Self {
+impl ParseWarning {
+    /// The line the warning is about, starting from 1, if known.
+    #[must_use]
+    pub const fn line(&self) -> Option<usize> {
+        self.position.line
+    }
+
+    /// The column the warning is about, starting from 1, if known.
+    #[must_use]
+    pub const fn column(&self) -> Option<usize> {
+        self.position.column
+    }
+
+    /// What looks off.
+    #[must_use]
+    pub const fn warning(&self) -> &Warning {
+        &self.warning
+    }
+}
+
+impl Display for ParseWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.position.describe("Warning", f)?;
+        write!(f, ": {}", self.warning)
+    }
+}
+
+/// The cards of a decklist along with the warnings about its lines.
+pub type WarnedDeck<T> = (Vec<CardEntry<T>>, Vec<ParseWarning>);
+
+/// Options that change how decklists are parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Marks the start of a trailing comment, like `3 Lightning Bolt # burn package`. The delimiter only counts when preceded by whitespace, so names containing it aren't broken. `None` disables inline comments.
+    pub comment_delimiter: Option<String>,
+    /// The amount given to lines that have no leading number, like `Sol Ring`. `None` makes such lines an error.
+    pub default_amount: Option<NonZeroU32>,
+    /// What to do when the same card name appears multiple times in a file.
+    pub duplicate_strategy: DuplicateStrategy,
+    /// Whether names that only differ in casing, like `Island` and `island`, count as duplicates.
+    pub case_insensitive_names: bool,
+    /// Whether names that only differ in their internal whitespace, like `Sol Ring` and `Sol  Ring`, count as duplicates.
+    pub collapse_name_whitespace: bool,
+    /// The characters allowed between the amount and the name. Non-whitespace separators, like the `x` in `4x Lightning Bolt`, only count as such right after the amount, so names like the one in `3 xenagos` are kept whole.
+    pub separators: Vec<char>,
+    /// Whether the amount comes before or after the name.
+    pub amount_position: AmountPosition,
+    /// A character used to group the digits of amounts, like the `,` in `1,000 Island`. It only counts as such after a digit and before exactly three digits, so `2,3 Island` is an error instead of 23 copies. Defaults to `None`, which disallows grouping.
+    pub digit_group_separator: Option<char>,
+    /// Whether amounts may be written with the decimal digits of other scripts, like the fullwidth `４`, instead of only `0` to `9`.
+    pub unicode_digits: bool,
+    /// Amounts above this are reported as `Warning::HighAmount` by `parse_file_with_warnings`. `None` disables the warning.
+    pub high_amount: Option<u32>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
         Self {
-            line: None,
-            column: None,
+            comment_delimiter: Some("#".to_string()),
+            default_amount: None,
+            duplicate_strategy: DuplicateStrategy::default(),
+            case_insensitive_names: false,
+            collapse_name_whitespace: false,
+            separators: vec![' ', '\t', 'x'],
+            amount_position: AmountPosition::default(),
+            digit_group_separator: None,
+            unicode_digits: false,
+            high_amount: Some(100),
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Describes the configured separators for `Error::UnexpectedChar`, if there are any.
+    fn expected_separator(&self) -> Option<String> {
+        let names: Vec<String> = self
+            .separators
+            .iter()
+            .map(|separator| match separator {
+                ' ' => "space".to_string(),
+                '\t' => "tab".to_string(),
+                separator => format!("`{separator}`"),
+            })
+            .collect();
+        match names.split_last() {
+            None => None,
+            Some((last, [])) => Some(format!("a number separator ({last})")),
+            Some((last, rest)) => Some(format!(
+                "a number separator ({} or {last})",
+                rest.join(", ")
+            )),
+        }
+    }
+
+    /// The ASCII digit `chr` stands for, if it's a digit these options allow.
+    fn ascii_digit(&self, chr: char) -> Option<char> {
+        if chr.is_ascii_digit() {
+            Some(chr)
+        } else if self.unicode_digits {
+            unicode_digit(chr)
+        } else {
+            None
+        }
+    }
+
+    /// Whether `chr` is the digit group separator, comes after a digit, and is followed by a group of exactly three digits, `rest` being everything after it.
+    fn is_digit_group_separator(&self, chr: char, number_str: &str, rest: &str) -> bool {
+        let mut rest = rest.chars();
+        let mut next_is_digit = || {
+            rest.next()
+                .is_some_and(|next| self.ascii_digit(next).is_some())
+        };
+        self.digit_group_separator == Some(chr)
+            && !number_str.is_empty()
+            && next_is_digit()
+            && next_is_digit()
+            && next_is_digit()
+            && !next_is_digit()
+    }
+
+    /// The string used to compare a card name against others when looking for duplicates.
+    fn name_key(&self, name: &str) -> String {
+        let name = if self.collapse_name_whitespace {
+            name.split_whitespace().collect::<Vec<_>>().join(" ")
+        } else {
+            name.to_owned()
+        };
+        if self.case_insensitive_names {
+            name.to_lowercase()
+        } else {
+            name
+        }
+    }
+}
+
+/// The first digit of each set of Unicode decimal digits outside of ASCII that `ParseOptions::unicode_digits` accepts. Every set has the digits from 0 to 9 in order.
+const UNICODE_ZEROES: [u32; 24] = [
+    0x0660, 0x06F0, 0x07C0, 0x0966, 0x09E6, 0x0A66, 0x0AE6, 0x0B66, 0x0BE6, 0x0C66, 0x0CE6, 0x0D66,
+    0x0DE6, 0x0E50, 0x0ED0, 0x0F20, 0x1040, 0x1090, 0x17E0, 0x1810, 0xFF10, 0x1D7CE, 0x1D7D8,
+    0x1D7E2,
+];
+
+/// The ASCII digit a Unicode decimal digit stands for, like `4` for the fullwidth `４`.
+fn unicode_digit(chr: char) -> Option<char> {
+    let code = u32::from(chr);
+    UNICODE_ZEROES
+        .iter()
+        .find(|zero| (**zero..**zero + 10).contains(&code))
+        .and_then(|zero| char::from_digit(code - zero, 10))
+}
+
+/// Where the amount of copies is written in each line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AmountPosition {
+    /// Before the name, like `4 Island` or `4x Island`.
+    #[default]
+    Leading,
+    /// After the name, like `Island 4` or `Island x4`. Only the last word of the line can be the amount, so `Fire 1 of 3 x2` is two copies of `Fire 1 of 3`. `ParseOptions::separators` isn't used.
+    Trailing,
+}
+
+/// What to do when the same card name appears multiple times in a file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicateStrategy {
+    /// Report `Error::NameMultipleTimes`.
+    #[default]
+    Error,
+    /// Add the amounts together into the first entry.
+    Sum,
+    /// Ignore every entry after the first one.
+    KeepFirst,
+    /// Replace the first entry with the latest one, keeping the first one's place in the deck.
+    KeepLast,
+}
+
+impl DuplicateStrategy {
+    /// Merges `duplicate` into `entry`, which was found first.
+    fn merge<T: GetCardInfo + Clone>(self, entry: &mut CardEntry<T>, duplicate: CardEntry<T>) {
+        match self {
+            Self::Error | Self::KeepFirst => (),
+            Self::Sum => entry.amount = entry.amount.saturating_add(duplicate.amount.get()),
+            Self::KeepLast => *entry = duplicate,
         }
     }
 }
 
-/// Parses a line of text
+/// Parses a line of text using the default `ParseOptions`
+/// # Errors
+/// Under the same situations as `parse_line_with_options`.
+pub fn parse_line<T: GetCardInfo + Clone>(string: &str) -> Result<CardEntry<T>, ParseError> {
+    parse_line_with_options(string, &ParseOptions::default())
+}
+
+/// Parses a line of text. Whitespace at the start of the line is ignored.
 /// # Errors
 /// - Whenever the supplied `GetCardInfo` implementation of `parse` fails.
-/// - Whenever a non-arabic digit character that is neither a space, a tab or an `x` is found during the parsing of the number.
+/// - Whenever a non-arabic digit character that isn't one of `options.separators` is found during the parsing of the number, unless it's the first character and `options.default_amount` is set.
 /// - If the characters found as the amount of copies of the card cannot be parsed into an i64.
 /// - If the characters found as the amount of copies of the card are parsed into the number 0.
+/// - If the characters found as the amount of copies of the card are parsed into a negative number.
+/// - If the characters found as the amount of copies of the card are parsed into a number larger than `u32::MAX`.
 /// - If the characters found as the name of the card is empty after being trimmed of spaces.
-pub fn parse_line<T: GetCardInfo + Clone>(string: &str) -> Result<CardEntry<T>, ParseError> {
+/// - If `options.amount_position` is `AmountPosition::Trailing`, the last word isn't an amount and `options.default_amount` isn't set.
+#[allow(clippy::too_many_lines)]
+pub fn parse_line_with_options<T: GetCardInfo + Clone>(
+    string: &str,
+    options: &ParseOptions,
+) -> Result<CardEntry<T>, ParseError> {
+    if options.amount_position == AmountPosition::Trailing {
+        return parse_trailing_line(string, options);
+    }
     let mut parserstate = ParserState::Numbering;
     let mut number_str = String::new();
     let mut name = String::new();
-    for (idx, chr) in string.char_indices() {
+    let mut number_end = None;
+    let mut name_offset = string.len();
+    // Whitespace before the amount, like the tab in a line pasted from a spreadsheet, is skipped
+    for (idx, chr) in string
+        .char_indices()
+        .skip_while(|(_, chr)| chr.is_whitespace())
+    {
         match parserstate {
             ParserState::Numbering => match chr {
-                chr @ ('0' | '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9') => {
-                    number_str.push(chr);
+                chr if options.ascii_digit(chr).is_some() => {
+                    number_str.extend(options.ascii_digit(chr));
+                    number_end = Some(idx + chr.len_utf8());
+                }
+                chr if options.is_digit_group_separator(
+                    chr,
+                    &number_str,
+                    &string[idx + chr.len_utf8()..],
+                ) => {}
+                'x' | 'X' if number_str.is_empty() => parserstate = ParserState::PrefixedNumbering,
+                chr if options.separators.contains(&chr) && chr.is_whitespace() => {
+                    parserstate = ParserState::Exing;
+                }
+                chr if options.separators.contains(&chr) => {
+                    name_offset = idx + chr.len_utf8();
+                    parserstate = ParserState::Naming;
+                }
+                chr if number_str.is_empty() && options.default_amount.is_some() => {
+                    name_offset = idx;
+                    name.push(chr);
+                    parserstate = ParserState::Naming;
                 }
-                ' ' | '\t' => parserstate = ParserState::Exing,
-                'x' => parserstate = ParserState::Naming,
                 chr => {
                     let mut expected = vec!["a digit".to_string()];
                     if !number_str.is_empty() {
-                        expected.push("a number separator (space, tab or `x`)".to_string());
+                        expected.extend(options.expected_separator());
                         expected.push("a card name".to_string());
                     }
                     return Err(ParseError {
@@ -187,13 +676,59 @@ pub fn parse_line<T: GetCardInfo + Clone>(string: &str) -> Result<CardEntry<T>,
                             line: None,
                             column: Some(idx + 1),
                         },
+                        source_line: None,
+                    });
+                }
+            },
+            ParserState::PrefixedNumbering => match chr {
+                chr if options.ascii_digit(chr).is_some() => {
+                    number_str.extend(options.ascii_digit(chr));
+                    number_end = Some(idx + chr.len_utf8());
+                }
+                chr if options.is_digit_group_separator(
+                    chr,
+                    &number_str,
+                    &string[idx + chr.len_utf8()..],
+                ) => {}
+                chr if !number_str.is_empty()
+                    && options.separators.contains(&chr)
+                    && chr.is_whitespace() =>
+                {
+                    parserstate = ParserState::Exing;
+                }
+                chr if !number_str.is_empty() && options.separators.contains(&chr) => {
+                    name_offset = idx + chr.len_utf8();
+                    parserstate = ParserState::Naming;
+                }
+                chr if number_str.is_empty() && options.default_amount.is_some() => {
+                    // The `x` was the start of a name like `Xerox`, not a prefix
+                    name_offset = idx - 1;
+                    name.push_str(&string[name_offset..idx + chr.len_utf8()]);
+                    parserstate = ParserState::Naming;
+                }
+                chr => {
+                    let mut expected = vec!["a digit after the `x`".to_string()];
+                    if !number_str.is_empty() {
+                        expected.extend(options.expected_separator());
+                    }
+                    return Err(ParseError {
+                        error: Error::UnexpectedChar {
+                            obtained: chr,
+                            expected,
+                        },
+                        position: LinePosition {
+                            line: None,
+                            column: Some(idx + 1),
+                        },
+                        source_line: None,
                     });
                 }
             },
             ParserState::Exing => match chr {
-                ' ' | '\t' => continue,
-                'x' => parserstate = ParserState::Naming,
+                ' ' | '\t' => {}
+                chr if options.separators.contains(&chr) && chr.is_whitespace() => {}
                 chr => {
+                    name_offset = idx;
                     name.push(chr);
                     parserstate = ParserState::Naming;
                 }
@@ -201,111 +736,1165 @@ pub fn parse_line<T: GetCardInfo + Clone>(string: &str) -> Result<CardEntry<T>,
             ParserState::Naming => name.push(chr),
         }
     }
-    let name = name.trim().to_owned();
+    let name_start = name_offset + (name.len() - name.trim_start().len()) + 1;
+    let name = match options.comment_delimiter.as_deref() {
+        Some(delimiter) if !delimiter.is_empty() => strip_inline_comment(&name, delimiter),
+        _ => &name,
+    }
+    .trim()
+    .to_owned();
 
-    let number = number_str.parse().map_err(|error| ParseError {
-        position: LinePosition {
-            line: None,
-            column: None,
-        },
-        error: Error::NotANumber {
-            string: number_str,
+    let number: i64 = match options.default_amount {
+        Some(amount) if number_str.is_empty() => amount.get().into(),
+        _ => number_str.parse().map_err(|error| ParseError {
+            position: LinePosition {
+                line: None,
+                column: None,
+            },
+            error: Error::NotANumber {
+                string: number_str,
+                error: Arc::new(error),
+            },
+            source_line: None,
+        })?,
+    };
+
+    finish_entry(name, number, number_end, name_start)
+}
+
+/// Parses a line whose amount comes after the name, for `AmountPosition::Trailing`.
+fn parse_trailing_line<T: GetCardInfo + Clone>(
+    string: &str,
+    options: &ParseOptions,
+) -> Result<CardEntry<T>, ParseError> {
+    let line = match options.comment_delimiter.as_deref() {
+        Some(delimiter) if !delimiter.is_empty() => strip_inline_comment(string, delimiter),
+        _ => string,
+    }
+    .trim_end();
+    let amount_start = line.rfind(char::is_whitespace).map_or(0, |idx| {
+        idx + line[idx..].chars().next().map_or(1, char::len_utf8)
+    });
+    let amount = &line[amount_start..];
+    let amount = amount.strip_prefix(['x', 'X']).unwrap_or(amount);
+    let mut digits = String::new();
+    for (idx, chr) in amount.char_indices() {
+        if options.is_digit_group_separator(chr, &digits, &amount[idx + chr.len_utf8()..]) {
+            continue;
+        }
+        if let Some(digit) = options.ascii_digit(chr) {
+            digits.push(digit);
+        } else {
+            digits.clear();
+            break;
+        }
+    }
+    let (name, number, number_end) = if !digits.is_empty() {
+        let number = digits.parse().map_err(|error| ParseError {
+            position: LinePosition {
+                line: None,
+                column: Some(amount_start + 1),
+            },
+            error: Error::NotANumber {
+                string: digits.clone(),
+                error: Arc::new(error),
+            },
+            source_line: None,
+        })?;
+        (&line[..amount_start], number, Some(line.len()))
+    } else if let Some(amount) = options.default_amount {
+        (line, amount.get().into(), None)
+    } else {
+        let (error, column) = line.char_indices().next_back().map_or(
+            (Error::NameIsEmpty, None),
+            |(idx, obtained)| {
+                let error = Error::UnexpectedChar {
+                    obtained,
+                    expected: vec!["a trailing amount".to_string()],
+                };
+                (error, Some(idx + 1))
+            },
+        );
+        return Err(ParseError {
             error,
-        },
-    })?;
+            position: LinePosition { line: None, column },
+            source_line: None,
+        });
+    };
+    let name_start = name.len() - name.trim_start().len() + 1;
+    finish_entry(name.trim().to_owned(), number, number_end, name_start)
+}
 
+/// Checks the amount and name read from a line and turns them into an entry.
+fn finish_entry<T: GetCardInfo + Clone>(
+    name: String,
+    number: i64,
+    number_end: Option<usize>,
+    name_start: usize,
+) -> Result<CardEntry<T>, ParseError> {
     if number == 0 {
         return Err(ParseError {
             error: Error::AmountIsZero { card_name: name },
             position: LinePosition {
                 line: None,
-                column: None,
+                column: number_end,
             },
+            source_line: None,
+        });
+    } else if number < 0 {
+        return Err(ParseError {
+            error: Error::AmountIsNegative {
+                card_name: name,
+                amount: number,
+            },
+            position: LinePosition {
+                line: None,
+                column: number_end,
+            },
+            source_line: None,
         });
     } else if name.is_empty() {
         return Err(ParseError {
             error: Error::NameIsEmpty,
             position: LinePosition {
                 line: None,
-                column: None,
+                column: Some(name_start),
             },
+            source_line: None,
         });
     }
+    let Some(amount) = u32::try_from(number).ok().and_then(NonZeroU32::new) else {
+        return Err(ParseError {
+            error: Error::AmountIsTooLarge {
+                card_name: name,
+                amount: number,
+            },
+            position: LinePosition {
+                line: None,
+                column: number_end,
+            },
+            source_line: None,
+        });
+    };
 
-    Ok(CardEntry {
-        card: T::parse(&name)?,
-        amount: number,
-    })
+    Ok(CardEntry::new(T::parse(&name)?, amount))
+}
+
+/// Turns a card name into a card through its `FromStr` implementation.
+///
+/// This lets `GetCardInfo::parse` be implemented as `parse_via_fromstr(string)`. Errors are reported as `Error::Custom` with the `FromStr` error's message.
+/// # Errors
+/// If `FromStr::from_str` fails.
+pub fn parse_via_fromstr<T: FromStr>(string: &str) -> Result<T, ParseError>
+where
+    T::Err: Display,
+{
+    string
+        .parse()
+        .map_err(|error: T::Err| ParseError::custom(error.to_string()))
+}
+
+/// Removes everything from the first `delimiter` that is preceded by whitespace onwards.
+fn strip_inline_comment<'a>(name: &'a str, delimiter: &str) -> &'a str {
+    name.match_indices(delimiter)
+        .find(|(idx, _)| name[..*idx].ends_with(char::is_whitespace))
+        .map_or(name, |(idx, _)| &name[..idx])
 }
 
 enum ParserState {
     Numbering,
+    /// Reading the amount after a leading `x`, like in `x4 Card`
+    PrefixedNumbering,
     Naming,
+    /// Skipping the whitespace between the amount and the name
     Exing,
 }
 
-/// Parses a file
+/// Parses a file using the default `ParseOptions`. Blank lines and lines whose first non-whitespace character is `#` are skipped.
 /// # Errors
-/// - If `parse_line` fails on any of the lines
-/// - If the same card name appears multiple times in the file
-/// - If the reader fails to read a line
+/// Under the same situations as `parse_file_with_options`.
+#[cfg(feature = "fs")]
 pub fn parse_file<T: GetCardInfo + Clone>(
     path: &PathBuf,
 ) -> Result<Vec<CardEntry<T>>, Vec<ParseError>> {
-    let file = File::open(path).map_err(|error| {
-        vec![ParseError {
-            position: LinePosition::void(),
-            error: Error::CantOpenFile {
-                path: path.clone(),
-                error,
-            },
-        }]
-    })?;
-    let mut reader = BufReader::new(file);
+    parse_file_with_options(path, &ParseOptions::default())
+}
+
+/// Parses a file. Blank lines and lines whose first non-whitespace character is `#` are skipped. A line ending in `\` continues on the next one, and errors point at the line it starts at.
+/// # Errors
+/// - If `parse_line_with_options` fails on any of the lines
+/// - If the same card name appears multiple times in the file and `options.duplicate_strategy` is `DuplicateStrategy::Error`
+/// - If the reader fails to read a line
+#[cfg(feature = "fs")]
+pub fn parse_file_with_options<T: GetCardInfo + Clone>(
+    path: &PathBuf,
+    options: &ParseOptions,
+) -> Result<Vec<CardEntry<T>>, Vec<ParseError>> {
+    let file = open_file(path).map_err(|error| vec![error])?;
+    all_or_nothing(parse_lines(
+        BufReader::new(file),
+        Some(path),
+        options,
+        &mut vec![],
+    ))
+}
+
+/// Parses a file using the default `ParseOptions`, along with the warnings about its lines.
+/// # Errors
+/// Under the same situations as `parse_file_with_warnings_with_options`.
+#[cfg(feature = "fs")]
+pub fn parse_file_with_warnings<T: GetCardInfo + Clone>(
+    path: &PathBuf,
+) -> Result<WarnedDeck<T>, Vec<ParseError>> {
+    parse_file_with_warnings_with_options(path, &ParseOptions::default())
+}
+
+/// Parses a file like `parse_file_with_options`, also returning warnings about lines that were parsed but look like mistakes, like amounts above `options.high_amount`.
+/// # Errors
+/// Under the same situations as `parse_file_with_options`.
+#[cfg(feature = "fs")]
+pub fn parse_file_with_warnings_with_options<T: GetCardInfo + Clone>(
+    path: &PathBuf,
+    options: &ParseOptions,
+) -> Result<WarnedDeck<T>, Vec<ParseError>> {
+    let file = open_file(path).map_err(|error| vec![error])?;
+    let mut warnings = vec![];
+    let cards = all_or_nothing(parse_lines(
+        BufReader::new(file),
+        Some(path),
+        options,
+        &mut warnings,
+    ))?;
+    Ok((cards, warnings))
+}
+
+/// Parses a file using the default `ParseOptions`, keeping every entry that could be parsed. Blank lines and lines whose first non-whitespace character is `#` are skipped.
+#[cfg(feature = "fs")]
+#[must_use]
+pub fn parse_file_lenient<T: GetCardInfo + Clone>(
+    path: &PathBuf,
+) -> (Vec<CardEntry<T>>, Vec<ParseError>) {
+    parse_file_lenient_with_options(path, &ParseOptions::default())
+}
+
+/// Parses a file, keeping every entry that could be parsed alongside the errors found in the rest. Entries whose name is a disallowed duplicate are not kept. Blank lines and lines whose first non-whitespace character is `#` are skipped.
+#[cfg(feature = "fs")]
+#[must_use]
+pub fn parse_file_lenient_with_options<T: GetCardInfo + Clone>(
+    path: &PathBuf,
+    options: &ParseOptions,
+) -> (Vec<CardEntry<T>>, Vec<ParseError>) {
+    match open_file(path) {
+        Ok(file) => parse_lines(BufReader::new(file), Some(path), options, &mut vec![]),
+        Err(error) => (vec![], vec![error]),
+    }
+}
+
+#[cfg(feature = "fs")]
+fn open_file(path: &PathBuf) -> Result<File, ParseError> {
+    File::open(path).map_err(|error| ParseError {
+        position: LinePosition::void(),
+        error: Error::CantOpenFile {
+            path: path.clone(),
+            error: Arc::new(error),
+        },
+        source_line: None,
+    })
+}
+
+/// Parses the lines of a reader using the default `ParseOptions`. Blank lines and lines whose first non-whitespace character is `#` are skipped.
+/// # Errors
+/// Under the same situations as `parse_reader_with_options`.
+pub fn parse_reader<T: GetCardInfo + Clone, R: BufRead>(
+    reader: R,
+) -> Result<Vec<CardEntry<T>>, Vec<ParseError>> {
+    parse_reader_with_options(reader, &ParseOptions::default())
+}
+
+/// Parses the lines of a reader. Blank lines and lines whose first non-whitespace character is `#` are skipped. A line ending in `\` continues on the next one, and errors point at the line it starts at.
+/// # Errors
+/// - If `parse_line_with_options` fails on any of the lines
+/// - If the same card name appears multiple times and `options.duplicate_strategy` is `DuplicateStrategy::Error`
+/// - If the reader fails to read a line
+pub fn parse_reader_with_options<T: GetCardInfo + Clone, R: BufRead>(
+    reader: R,
+    options: &ParseOptions,
+) -> Result<Vec<CardEntry<T>>, Vec<ParseError>> {
+    all_or_nothing(parse_lines(reader, None, options, &mut vec![]))
+}
+
+/// Parses a whole decklist using the default `ParseOptions`. Blank lines and lines whose first non-whitespace character is `#` are skipped.
+/// # Errors
+/// Under the same situations as `parse_str_with_options`.
+pub fn parse_str<T: GetCardInfo + Clone>(
+    input: &str,
+) -> Result<Vec<CardEntry<T>>, Vec<ParseError>> {
+    parse_str_with_options(input, &ParseOptions::default())
+}
+
+/// Parses a whole decklist. Lines may end in `\n` or `\r\n`, and the last line doesn't need a line ending.
+///
+/// Blank lines and lines whose first non-whitespace character is `#` are skipped. A line ending in `\` continues on the next one, and errors point at the line it starts at.
+/// # Errors
+/// - If `parse_line_with_options` fails on any of the lines
+/// - If the same card name appears multiple times and `options.duplicate_strategy` is `DuplicateStrategy::Error`
+pub fn parse_str_with_options<T: GetCardInfo + Clone>(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<Vec<CardEntry<T>>, Vec<ParseError>> {
+    all_or_nothing(parse_lines(input.as_bytes(), None, options, &mut vec![]))
+}
+
+/// The UTF-8 byte order mark some editors put at the start of files.
+const BYTE_ORDER_MARK: char = '\u{FEFF}';
+
+/// Parses every line in `reader`, adding the warnings about them to `warnings`. A byte order mark at the start of the first line is ignored. `path` is only used to report errors.
+fn parse_lines<T: GetCardInfo + Clone, R: BufRead>(
+    mut reader: R,
+    path: Option<&PathBuf>,
+    options: &ParseOptions,
+    warnings: &mut Vec<ParseWarning>,
+) -> (Vec<CardEntry<T>>, Vec<ParseError>) {
     let mut cards = vec![];
-    let mut used_names = vec![];
-    let mut line_idx = 0;
+    let mut used_names = UsedNames::new();
+    let mut next_line_idx = 1;
     let mut errors = vec![];
     loop {
-        line_idx += 1;
+        let line_idx = next_line_idx;
         let mut line = String::new();
-        match reader.read_line(&mut line) {
+        let read = read_joined_line(&mut reader, &mut line);
+        if line_idx == 1 && line.starts_with(BYTE_ORDER_MARK) {
+            line.remove(0);
+        }
+        next_line_idx += read.as_ref().map_or(1, |lines| *lines);
+        match read {
             Ok(0) => break,
-            Ok(_) if !line.trim().is_empty() => match parse_line::<T>(&line) {
-                Ok(entry) => {
-                    let name = entry.card.get_name().to_owned();
-                    if used_names.contains(&name) {
-                        errors.push(ParseError {
-                            position: LinePosition {
-                                line: Some(line_idx),
-                                column: None,
-                            },
-                            error: Error::NameMultipleTimes { name },
-                        });
-                    } else {
-                        used_names.push(name);
-                        cards.push(entry);
+            Ok(_) if !is_blank_or_comment(&line) => {
+                match parse_line_with_options::<T>(&line, options) {
+                    Ok(entry) => {
+                        add_warnings(warnings, &line, line_idx, &entry, options);
+                        add_entry(
+                            &mut cards,
+                            &mut used_names,
+                            &mut errors,
+                            entry,
+                            line_idx,
+                            options,
+                        );
                     }
+                    Err(error) => errors.push(error.at_line(line_idx).with_source_line(&line)),
                 }
-                Err(error) => errors.push(error.at_line(line_idx)),
+            }
+            Ok(_) => {}
+            Err(error) => errors.push(ParseError {
+                position: LinePosition {
+                    line: Some(line_idx),
+                    column: None,
+                },
+                error: Error::CouldntReadLine {
+                    path: path.cloned(),
+                    line: line_idx,
+                    error: Arc::new(error),
+                },
+                source_line: None,
+            }),
+        }
+    }
+    (cards, errors)
+}
+
+/// The index in a `Vec<CardEntry>` of the entry for each name key, as given by `ParseOptions::name_key`.
+type UsedNames = HashMap<String, usize>;
+
+/// Reads a line into `line` like `BufRead::read_line`, joining the lines that follow when it ends in a `\`, which is removed. Lines ending in `\\` are kept as they are. Returns how many lines were read, which is 0 at the end of the reader.
+fn read_joined_line<R: BufRead>(reader: &mut R, line: &mut String) -> io::Result<usize> {
+    let mut lines = 0;
+    while reader.read_line(line)? > 0 {
+        lines += 1;
+        let content = line.trim_end_matches(['\n', '\r']);
+        if !content.ends_with('\\') || content.ends_with("\\\\") {
+            break;
+        }
+        line.truncate(content.len() - 1);
+    }
+    Ok(lines)
+}
+
+/// Adds the warnings about `entry`, which was parsed from `line`, to `warnings`.
+fn add_warnings<T: GetCardInfo + Clone>(
+    warnings: &mut Vec<ParseWarning>,
+    line: &str,
+    line_idx: usize,
+    entry: &CardEntry<T>,
+    options: &ParseOptions,
+) {
+    let line = match options.comment_delimiter.as_deref() {
+        Some(delimiter) if !delimiter.is_empty() => strip_inline_comment(line, delimiter),
+        _ => line,
+    }
+    .trim_end();
+    let amount_start = match options.amount_position {
+        AmountPosition::Leading => line.len() - line.trim_start().len(),
+        AmountPosition::Trailing => line.rfind(char::is_whitespace).map_or(0, |idx| {
+            idx + line[idx..].chars().next().map_or(1, char::len_utf8)
+        }),
+    };
+    let amount = line[amount_start..]
+        .split_whitespace()
+        .next()
+        .unwrap_or_default();
+    let position = LinePosition {
+        line: Some(line_idx),
+        column: Some(amount_start + 1),
+    };
+    let card_name = entry.card.get_name().to_owned();
+    if options
+        .high_amount
+        .is_some_and(|high_amount| entry.amount.get() > high_amount)
+    {
+        warnings.push(ParseWarning {
+            position,
+            warning: Warning::HighAmount {
+                card_name: card_name.clone(),
+                amount: entry.amount.get(),
             },
-            Ok(_) => continue,
+        });
+    }
+    if entry.amount.get() == 1
+        && (amount.eq_ignore_ascii_case("1x") || amount.eq_ignore_ascii_case("x1"))
+    {
+        warnings.push(ParseWarning {
+            position,
+            warning: Warning::RedundantCount { card_name },
+        });
+    }
+}
+
+/// Adds an entry parsed from line `line_idx` to `cards`, handling it according to `options.duplicate_strategy` if its name was already used.
+fn add_entry<T: GetCardInfo + Clone>(
+    cards: &mut Vec<CardEntry<T>>,
+    used_names: &mut UsedNames,
+    errors: &mut Vec<ParseError>,
+    entry: CardEntry<T>,
+    line_idx: usize,
+    options: &ParseOptions,
+) {
+    let key = options.name_key(entry.card.get_name());
+    match used_names.get(&key).copied() {
+        None => {
+            used_names.insert(key, cards.len());
+            cards.push(entry);
+        }
+        Some(idx) if options.duplicate_strategy == DuplicateStrategy::Error => {
+            errors.push(ParseError {
+                position: LinePosition {
+                    line: Some(line_idx),
+                    column: None,
+                },
+                error: Error::NameMultipleTimes {
+                    name: entry.card.get_name().to_owned(),
+                    original: cards[idx].card.get_name().to_owned(),
+                },
+                source_line: None,
+            });
+        }
+        Some(idx) => options.duplicate_strategy.merge(&mut cards[idx], entry),
+    }
+}
+
+/// Parses a file using the default `ParseOptions`, parsing its lines in parallel. Blank lines and lines whose first non-whitespace character is `#` are skipped.
+/// # Errors
+/// Under the same situations as `parse_file_parallel_with_options`.
+#[cfg(feature = "rayon")]
+pub fn parse_file_parallel<T: GetCardInfo + Clone + Send>(
+    path: &PathBuf,
+) -> Result<Vec<CardEntry<T>>, Vec<ParseError>> {
+    parse_file_parallel_with_options(path, &ParseOptions::default())
+}
+
+/// Parses a file like `parse_file_with_options`, but reads every line first and then parses them in parallel.
+///
+/// Duplicate names are resolved afterwards in file order, so the result is the same as the one `parse_file_with_options` would give.
+/// # Errors
+/// Under the same situations as `parse_file_with_options`.
+#[cfg(feature = "rayon")]
+pub fn parse_file_parallel_with_options<T: GetCardInfo + Clone + Send>(
+    path: &PathBuf,
+    options: &ParseOptions,
+) -> Result<Vec<CardEntry<T>>, Vec<ParseError>> {
+    use rayon::prelude::*;
+
+    let mut reader = BufReader::new(open_file(path).map_err(|error| vec![error])?);
+    let mut lines = vec![];
+    let mut errors = vec![];
+    let mut next_line_idx = 1;
+    loop {
+        let line_idx = next_line_idx;
+        let mut line = String::new();
+        let read = read_joined_line(&mut reader, &mut line);
+        next_line_idx += read.as_ref().map_or(1, |lines| *lines);
+        match read {
+            Ok(0) => break,
+            Ok(_) => {
+                if line_idx == 1 && line.starts_with(BYTE_ORDER_MARK) {
+                    line.remove(0);
+                }
+                lines.push((line_idx, line));
+            }
             Err(error) => errors.push(ParseError {
                 position: LinePosition {
                     line: Some(line_idx),
                     column: None,
                 },
                 error: Error::CouldntReadLine {
-                    path: path.clone(),
+                    path: Some(path.clone()),
                     line: line_idx,
-                    error,
+                    error: Arc::new(error),
                 },
+                source_line: None,
             }),
         }
     }
+
+    let parsed: Vec<_> = lines
+        .par_iter()
+        .filter(|(_, line)| !is_blank_or_comment(line))
+        .map(|(line_idx, line)| {
+            (
+                *line_idx,
+                parse_line_with_options::<T>(line, options)
+                    .map_err(|error| error.at_line(*line_idx).with_source_line(line)),
+            )
+        })
+        .collect();
+
+    let mut cards = vec![];
+    let mut used_names = UsedNames::new();
+    for (line_idx, result) in parsed {
+        match result {
+            Ok(entry) => add_entry(
+                &mut cards,
+                &mut used_names,
+                &mut errors,
+                entry,
+                line_idx,
+                options,
+            ),
+            Err(error) => errors.push(error),
+        }
+    }
+    errors.sort_by_key(ParseError::line);
+    all_or_nothing((cards, errors))
+}
+
+/// Only returns the parsed entries if there were no errors at all.
+fn all_or_nothing<T: GetCardInfo + Clone>(
+    (cards, errors): (Vec<CardEntry<T>>, Vec<ParseError>),
+) -> Result<Vec<CardEntry<T>>, Vec<ParseError>> {
     if errors.is_empty() {
         Ok(cards)
     } else {
         Err(errors)
     }
 }
+
+/// Whether a line should be skipped entirely, which is the case for lines that are blank or are comments.
+fn is_blank_or_comment(line: &str) -> bool {
+    let line = line.trim();
+    line.is_empty() || line.starts_with('#')
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error::Error as _, io::Cursor};
+
+    use super::*;
+    use crate::test_card::TestCard;
+
+    fn parse(line: &str, options: &ParseOptions) -> Result<(String, u32), ParseError> {
+        parse_line_with_options::<TestCard>(line, options)
+            .map(|entry| (entry.card.get_name().to_owned(), entry.amount.get()))
+    }
+
+    fn entry(name: &str, amount: u32) -> (String, u32) {
+        (name.to_owned(), amount)
+    }
+
+    fn names(cards: &[CardEntry<TestCard>]) -> Vec<(String, u32)> {
+        cards
+            .iter()
+            .map(|entry| entry.card.get_name().to_owned())
+            .zip(cards.iter().map(|entry| entry.amount.get()))
+            .collect()
+    }
+
+    fn error_of(line: &str, options: &ParseOptions) -> ParseError {
+        let Err(error) = parse(line, options) else {
+            panic!("`{line}` should fail to parse");
+        };
+        error
+    }
+
+    #[test]
+    fn parses_an_amount_and_a_name() {
+        let options = ParseOptions::default();
+        assert_eq!(
+            parse("4 Lightning Bolt", &options).unwrap(),
+            entry("Lightning Bolt", 4)
+        );
+        assert_eq!(parse("12\tIsland", &options).unwrap(), entry("Island", 12));
+    }
+
+    #[test]
+    fn negative_amounts_are_rejected() {
+        let error = error_of("-3 Island", &ParseOptions::default());
+        assert!(matches!(
+            error.error(),
+            Error::AmountIsNegative { .. } | Error::UnexpectedChar { obtained: '-', .. }
+        ));
+    }
+
+    #[test]
+    fn amounts_larger_than_u32_are_rejected() {
+        let error = error_of("4294967296 Island", &ParseOptions::default());
+        assert!(matches!(
+            error.error(),
+            Error::AmountIsTooLarge {
+                amount: 4_294_967_296,
+                ..
+            }
+        ));
+        let error = error_of("99999999999999999999 Island", &ParseOptions::default());
+        assert!(matches!(error.error(), Error::NotANumber { .. }));
+        assert_eq!(
+            parse("4294967295 Island", &ParseOptions::default()).unwrap(),
+            entry("Island", u32::MAX)
+        );
+    }
+
+    #[test]
+    fn zero_and_empty_names_have_a_column() {
+        let error = error_of("0 Island", &ParseOptions::default());
+        assert!(matches!(error.error(), Error::AmountIsZero { .. }));
+        assert_eq!(error.column(), Some(1));
+        let error = error_of("3 ", &ParseOptions::default());
+        assert!(matches!(error.error(), Error::NameIsEmpty));
+        assert!(error.column().is_some());
+    }
+
+    #[test]
+    fn comments_are_only_whole_lines_or_after_whitespace() {
+        let options = ParseOptions::default();
+        assert_eq!(
+            parse("2 Island # basics", &options).unwrap(),
+            entry("Island", 2)
+        );
+        assert_eq!(parse("1 Sol#Ring", &options).unwrap(), entry("Sol#Ring", 1));
+        let options = ParseOptions {
+            comment_delimiter: None,
+            ..ParseOptions::default()
+        };
+        assert_eq!(
+            parse("2 Island # basics", &options).unwrap(),
+            entry("Island # basics", 2)
+        );
+    }
+
+    #[test]
+    fn default_amount_is_used_for_lines_without_one() {
+        let options = ParseOptions {
+            default_amount: NonZeroU32::new(1),
+            ..ParseOptions::default()
+        };
+        assert_eq!(parse("Sol Ring", &options).unwrap(), entry("Sol Ring", 1));
+        assert_eq!(
+            parse("  Sol Ring  ", &options).unwrap(),
+            entry("Sol Ring", 1)
+        );
+        assert!(parse("Sol Ring", &ParseOptions::default()).is_err());
+        assert!(is_blank_or_comment("   \t\n"));
+    }
+
+    #[test]
+    fn parse_reader_reads_any_buffered_reader() {
+        let cards =
+            parse_reader::<TestCard, _>(Cursor::new("2 Island\n# basics\n1 Sol Ring\n")).unwrap();
+        assert_eq!(
+            names(&cards),
+            vec![entry("Island", 2), entry("Sol Ring", 1)]
+        );
+    }
+
+    #[test]
+    fn parse_str_accepts_crlf_and_a_missing_trailing_newline() {
+        let cards = parse_str::<TestCard>("2 Island\r\n1 Sol Ring").unwrap();
+        assert_eq!(
+            names(&cards),
+            vec![entry("Island", 2), entry("Sol Ring", 1)]
+        );
+    }
+
+    #[test]
+    fn byte_order_marks_are_skipped() {
+        let cards = parse_str::<TestCard>("\u{FEFF}2 Island\n").unwrap();
+        assert_eq!(names(&cards), vec![entry("Island", 2)]);
+        let cards = parse_reader::<TestCard, _>(Cursor::new("\u{FEFF}2 Island\n")).unwrap();
+        assert_eq!(names(&cards), vec![entry("Island", 2)]);
+    }
+
+    #[test]
+    fn digit_group_separators() {
+        let options = ParseOptions {
+            digit_group_separator: Some(','),
+            ..ParseOptions::default()
+        };
+        assert_eq!(
+            parse("1,000 Island", &options).unwrap(),
+            entry("Island", 1000)
+        );
+        assert!(parse("abc Island", &options).is_err());
+        let options = ParseOptions {
+            digit_group_separator: Some('_'),
+            ..ParseOptions::default()
+        };
+        assert_eq!(
+            parse("1_000 Island", &options).unwrap(),
+            entry("Island", 1000)
+        );
+        let options = ParseOptions {
+            digit_group_separator: None,
+            ..ParseOptions::default()
+        };
+        assert!(parse("1,000 Island", &options).is_err());
+    }
+
+    #[test]
+    fn unicode_digits_are_opt_in() {
+        assert!(parse("４ Island", &ParseOptions::default()).is_err());
+        let options = ParseOptions {
+            unicode_digits: true,
+            ..ParseOptions::default()
+        };
+        assert_eq!(parse("４ Island", &options).unwrap(), entry("Island", 4));
+        assert_eq!(parse("٣ Island", &options).unwrap(), entry("Island", 3));
+    }
+
+    #[test]
+    fn lines_ending_in_a_backslash_continue() {
+        let cards = parse_str::<TestCard>("1 Very Long \\\nCard Name\n2 Island\n").unwrap();
+        assert_eq!(
+            names(&cards),
+            vec![entry("Very Long Card Name", 1), entry("Island", 2)]
+        );
+        let cards = parse_str::<TestCard>("1 Back\\\\\n2 Island\n").unwrap();
+        assert_eq!(
+            names(&cards),
+            vec![entry("Back\\\\", 1), entry("Island", 2)]
+        );
+        let Err(errors) = parse_str::<TestCard>("1 Island\n0 Long \\\nName\n0 Bolt\n") else {
+            panic!("the amounts are zero");
+        };
+        assert_eq!(errors[0].line(), Some(2));
+        assert_eq!(errors[1].line(), Some(4));
+    }
+
+    #[test]
+    fn x_amounts() {
+        let options = ParseOptions::default();
+        assert_eq!(parse("4x Card", &options).unwrap(), entry("Card", 4));
+        assert_eq!(parse("x4 Card", &options).unwrap(), entry("Card", 4));
+        assert_eq!(parse("4x Bolt", &options).unwrap(), entry("Bolt", 4));
+        assert_eq!(parse("3 Xenagos", &options).unwrap(), entry("Xenagos", 3));
+        assert_eq!(parse("3 xenagos", &options).unwrap(), entry("xenagos", 3));
+        let error = error_of("x Card", &options);
+        assert!(matches!(
+            error.error(),
+            Error::UnexpectedChar { obtained: ' ', .. }
+        ));
+        assert_eq!(error.column(), Some(2));
+    }
+
+    #[test]
+    fn custom_separators() {
+        let options = ParseOptions {
+            separators: vec![':'],
+            ..ParseOptions::default()
+        };
+        assert_eq!(parse("3:Island", &options).unwrap(), entry("Island", 3));
+        assert!(parse("3 Island", &options).is_err());
+    }
+
+    #[test]
+    fn trailing_amounts() {
+        let options = ParseOptions {
+            amount_position: AmountPosition::Trailing,
+            ..ParseOptions::default()
+        };
+        assert_eq!(parse("Island 4", &options).unwrap(), entry("Island", 4));
+        assert_eq!(parse("Island x4", &options).unwrap(), entry("Island", 4));
+        assert_eq!(
+            parse("Lightning Bolt 4", &options).unwrap(),
+            entry("Lightning Bolt", 4)
+        );
+        assert_eq!(
+            parse("Fire 1 of 3 2", &options).unwrap(),
+            entry("Fire 1 of 3", 2)
+        );
+        assert_eq!(
+            parse("Fire 1 of 3", &options).unwrap(),
+            entry("Fire 1 of", 3)
+        );
+        assert!(parse("Lightning Bolt", &options).is_err());
+    }
+
+    #[test]
+    fn whitespace_before_the_amount_is_skipped() {
+        let options = ParseOptions::default();
+        assert_eq!(parse("\t4 Island", &options).unwrap(), entry("Island", 4));
+        assert_eq!(parse("  4 Island", &options).unwrap(), entry("Island", 4));
+        assert_eq!(parse("4\tIsland", &options).unwrap(), entry("Island", 4));
+    }
+
+    #[test]
+    fn errors_have_their_position_and_line() {
+        let Err(errors) = parse_str::<TestCard>("2 Island\n2 Sol Ring\nfour Island\n") else {
+            panic!("the third line is invalid");
+        };
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].error(), Error::UnexpectedChar { .. }));
+        assert_eq!(errors[0].line(), Some(3));
+        assert_eq!(errors[0].column(), Some(1));
+        assert_eq!(errors[0].source_line(), Some("four Island"));
+        assert!(errors[0].to_string().contains("four Island"));
+        assert_eq!(
+            error_of("four Island", &ParseOptions::default()).source_line(),
+            None
+        );
+    }
+
+    #[test]
+    fn errors_can_be_cloned_and_serialized() {
+        let errors = [
+            error_of("x Card", &ParseOptions::default()),
+            error_of("0 Island", &ParseOptions::default()),
+            error_of("3 ", &ParseOptions::default()),
+            error_of("99999999999999999999 Island", &ParseOptions::default()),
+            ParseError {
+                position: LinePosition::void(),
+                source_line: None,
+                error: Error::CantOpenFile {
+                    path: PathBuf::from("deck.txt"),
+                    error: Arc::new(io::Error::new(io::ErrorKind::NotFound, "missing")),
+                },
+            },
+            ParseError {
+                position: LinePosition {
+                    line: Some(3),
+                    column: None,
+                },
+                source_line: None,
+                error: Error::CouldntReadLine {
+                    path: None,
+                    line: 3,
+                    error: Arc::new(io::Error::new(io::ErrorKind::InvalidData, "not UTF-8")),
+                },
+            },
+            ParseError {
+                position: LinePosition::void(),
+                source_line: None,
+                error: Error::NameMultipleTimes {
+                    name: "island".to_owned(),
+                    original: "Island".to_owned(),
+                },
+            },
+        ];
+        for error in &errors {
+            let json = serde_json::to_string(error).unwrap();
+            let read: ParseError = serde_json::from_str(&json).unwrap();
+            assert_eq!(read.to_string(), error.to_string());
+            assert_eq!(read.line(), error.line());
+            assert_eq!(read.column(), error.column());
+            assert_eq!(format!("{:?}", error.clone()), format!("{error:?}"));
+        }
+    }
+
+    #[test]
+    fn deserialized_not_a_number_errors_keep_the_message() {
+        let error = error_of("99999999999999999999 Island", &ParseOptions::default());
+        let json = serde_json::to_string(&error).unwrap();
+        let read: ParseError = serde_json::from_str(&json).unwrap();
+        let Error::NotANumber { string, error } = read.error() else {
+            panic!("the amount doesn't fit in an i64");
+        };
+        assert_eq!(string, "99999999999999999999");
+        assert_eq!(error.to_string(), "number too large to fit in target type");
+        assert!(read.error().source().is_some());
+    }
+
+    #[test]
+    fn parse_via_fromstr_reports_custom_errors() {
+        assert_eq!(parse_via_fromstr::<u8>("12").unwrap(), 12);
+        let Err(error) = parse_via_fromstr::<u8>("twelve") else {
+            panic!("twelve isn't a u8");
+        };
+        assert!(matches!(error.error(), Error::Custom { .. }));
+        assert_eq!(error.error().to_string(), "invalid digit found in string");
+    }
+
+    #[test]
+    fn custom_errors_display_their_message() {
+        let error = Error::Custom {
+            message: "Unknown set".to_owned(),
+        };
+        assert_eq!(error.to_string(), "Unknown set");
+        let Err(errors) = parse_str::<TestCard>("2 Island\n2 Is!land\n") else {
+            panic!("`Is!land` isn't a card");
+        };
+        assert!(matches!(errors[0].error(), Error::Custom { .. }));
+        assert_eq!(errors[0].line(), Some(2));
+        assert_eq!(errors[0].error().to_string(), "Is!land isn't a card");
+    }
+
+    #[test]
+    fn errors_keep_their_source() {
+        let Err(error) = "many".parse::<u32>() else {
+            panic!("many isn't a number");
+        };
+        let error = Error::NotANumber {
+            string: "many".to_owned(),
+            error: Arc::new(error),
+        };
+        assert!(error.source().is_some());
+        let error = Error::CantOpenFile {
+            path: PathBuf::from("deck.txt"),
+            error: Arc::new(io::Error::new(io::ErrorKind::NotFound, "missing")),
+        };
+        assert!(error.source().is_some());
+        assert!(Error::NameIsEmpty.source().is_none());
+        let error = ParseError {
+            position: LinePosition::void(),
+            source_line: None,
+            error: Error::NameIsEmpty,
+        };
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn duplicates_in_large_decklists_are_merged() {
+        use std::fmt::Write;
+
+        let contents = (0..100_000).fold(String::new(), |mut contents, idx| {
+            writeln!(contents, "1 Card {}", idx % 50_000).unwrap();
+            contents
+        });
+        let options = ParseOptions {
+            duplicate_strategy: DuplicateStrategy::Sum,
+            ..ParseOptions::default()
+        };
+        let cards = parse_str_with_options::<TestCard>(&contents, &options).unwrap();
+        assert_eq!(cards.len(), 50_000);
+        assert!(cards.iter().all(|entry| entry.amount.get() == 2));
+        assert_eq!(cards[49_999].card.get_name(), "Card 49999");
+        let Err(errors) = parse_str::<TestCard>(&contents) else {
+            panic!("every name is repeated");
+        };
+        assert_eq!(errors.len(), 50_000);
+        assert_eq!(errors[0].line(), Some(50_001));
+    }
+
+    #[cfg(feature = "fs")]
+    mod files {
+        use super::*;
+        use crate::test_card::temp_dir;
+
+        fn write(test: &str, contents: &str) -> PathBuf {
+            let path = temp_dir(test).join("deck.txt");
+            std::fs::write(&path, contents).unwrap();
+            path
+        }
+
+        #[test]
+        fn parse_file_skips_comments_and_blank_lines() {
+            let path = write("parse_file", "# main\n\n2 Island\n   # note\n1 Sol Ring\n");
+            let cards = parse_file::<TestCard>(&path).unwrap();
+            assert_eq!(
+                names(&cards),
+                vec![entry("Island", 2), entry("Sol Ring", 1)]
+            );
+        }
+
+        #[test]
+        fn parse_file_joins_continued_lines() {
+            let path = write(
+                "parse_file_continued",
+                "1 Very Long \\\nCard Name\n0 Bolt\n",
+            );
+            let Err(errors) = parse_file::<TestCard>(&path) else {
+                panic!("the amount is zero");
+            };
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].line(), Some(3));
+            let path = write("parse_file_continued_ok", "1 Very Long \\\nCard Name\n");
+            let cards = parse_file::<TestCard>(&path).unwrap();
+            assert_eq!(names(&cards), vec![entry("Very Long Card Name", 1)]);
+        }
+
+        #[test]
+        fn duplicate_strategies() {
+            let path = write("duplicates", "2 Forest\n3 Forest\n");
+            let Err(errors) = parse_file::<TestCard>(&path) else {
+                panic!("duplicates are errors by default");
+            };
+            assert!(matches!(errors[0].error(), Error::NameMultipleTimes { .. }));
+            assert_eq!(errors[0].line(), Some(2));
+            for (strategy, amount) in [
+                (DuplicateStrategy::Sum, 5),
+                (DuplicateStrategy::KeepFirst, 2),
+                (DuplicateStrategy::KeepLast, 3),
+            ] {
+                let options = ParseOptions {
+                    duplicate_strategy: strategy,
+                    ..ParseOptions::default()
+                };
+                let cards = parse_file_with_options::<TestCard>(&path, &options).unwrap();
+                assert_eq!(names(&cards), vec![entry("Forest", amount)], "{strategy:?}");
+            }
+        }
+
+        #[test]
+        fn case_insensitive_duplicates() {
+            let path = write("case", "1 Island\n1 island\n1 Éclair\n1 éclair\n");
+            let cards = parse_file::<TestCard>(&path).unwrap();
+            assert_eq!(cards.len(), 4);
+            let options = ParseOptions {
+                case_insensitive_names: true,
+                duplicate_strategy: DuplicateStrategy::Sum,
+                ..ParseOptions::default()
+            };
+            let cards = parse_file_with_options::<TestCard>(&path, &options).unwrap();
+            assert_eq!(names(&cards), vec![entry("Island", 2), entry("Éclair", 2)]);
+            let options = ParseOptions {
+                case_insensitive_names: true,
+                ..ParseOptions::default()
+            };
+            let Err(errors) = parse_file_with_options::<TestCard>(&path, &options) else {
+                panic!("`island` is a duplicate of `Island`");
+            };
+            let Error::NameMultipleTimes { name, original } = &errors[0].error() else {
+                panic!("the error should be about the duplicate");
+            };
+            assert_eq!((name.as_str(), original.as_str()), ("island", "Island"));
+        }
+
+        #[test]
+        fn parse_file_reports_missing_files() {
+            let path = temp_dir("missing_file").join("missing.txt");
+            let Err(errors) = parse_file::<TestCard>(&path) else {
+                panic!("the file doesn't exist");
+            };
+            assert!(matches!(errors[0].error(), Error::CantOpenFile { .. }));
+            let (cards, errors) = parse_file_lenient::<TestCard>(&path);
+            assert!(cards.is_empty());
+            assert!(matches!(errors[0].error(), Error::CantOpenFile { .. }));
+        }
+
+        #[test]
+        fn parse_file_reports_the_line_text() {
+            let path = write("line_text", "2 Island\nfour Island\n");
+            let Err(errors) = parse_file::<TestCard>(&path) else {
+                panic!("the second line is invalid");
+            };
+            assert_eq!(errors[0].source_line(), Some("four Island"));
+            assert!(errors[0].to_string().contains("four Island"));
+        }
+
+        #[test]
+        fn lenient_parsing_keeps_the_valid_lines() {
+            let path = write("lenient", "2 Island\nfour Island\n1 Sol Ring\n");
+            let (cards, errors) = parse_file_lenient::<TestCard>(&path);
+            assert_eq!(
+                names(&cards),
+                vec![entry("Island", 2), entry("Sol Ring", 1)]
+            );
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].line(), Some(2));
+        }
+
+        #[test]
+        fn warnings_for_high_and_redundant_amounts() {
+            let path = write("warnings", "9999 Island\n1x Sol Ring\n4 Bolt\n");
+            let (cards, warnings) = parse_file_with_warnings::<TestCard>(&path).unwrap();
+            assert_eq!(
+                names(&cards),
+                vec![
+                    entry("Island", 9999),
+                    entry("Sol Ring", 1),
+                    entry("Bolt", 4)
+                ]
+            );
+            assert_eq!(warnings.len(), 2);
+            assert!(matches!(
+                warnings[0].warning(),
+                Warning::HighAmount { amount: 9999, .. }
+            ));
+            assert_eq!(warnings[0].line(), Some(1));
+            assert!(matches!(
+                warnings[1].warning(),
+                Warning::RedundantCount { .. }
+            ));
+            assert_eq!(warnings[1].line(), Some(2));
+        }
+
+        #[cfg(feature = "rayon")]
+        #[test]
+        fn parallel_parsing_matches_sequential_parsing() {
+            use std::fmt::Write;
+
+            let contents = (1..=50_000).fold(String::new(), |mut contents, idx| {
+                writeln!(contents, "{} Card {idx}", idx % 9 + 1).unwrap();
+                contents
+            });
+            let path = write("parallel", &contents);
+            let parallel = parse_file_parallel::<TestCard>(&path).unwrap();
+            assert_eq!(parallel.len(), 50_000);
+            assert_eq!(
+                names(&parallel),
+                names(&parse_file::<TestCard>(&path).unwrap())
+            );
+            let path = write("parallel_errors", "2 Island\nfour Island\n2 Island\n");
+            let Err(errors) = parse_file_parallel::<TestCard>(&path) else {
+                panic!("the file has errors");
+            };
+            assert_eq!(errors.len(), 2);
+        }
+    }
+
+    #[test]
+    fn digit_grouping_is_opt_in() {
+        assert!(parse("1,000 Island", &ParseOptions::default()).is_err());
+        let options = ParseOptions {
+            digit_group_separator: Some(','),
+            ..ParseOptions::default()
+        };
+        assert_eq!(
+            parse("1,000 Island", &options).unwrap(),
+            entry("Island", 1000)
+        );
+        assert_eq!(
+            parse("1,000,000 Island", &options).unwrap(),
+            entry("Island", 1_000_000)
+        );
+    }
+
+    #[test]
+    fn digit_groups_need_three_digits() {
+        let options = ParseOptions {
+            digit_group_separator: Some(','),
+            ..ParseOptions::default()
+        };
+        assert!(parse("2,3 Island", &options).is_err());
+        assert!(parse("2,30 Island", &options).is_err());
+        assert!(parse("2,3000 Island", &options).is_err());
+        assert!(parse(",300 Island", &options).is_err());
+    }
+}
@@ -0,0 +1,326 @@
+use std::{
+    borrow::Cow,
+    fmt::{Display, Write},
+    num::{NonZeroU32, ParseIntError},
+};
+
+use quick_xml::{
+    escape::escape,
+    events::{BytesStart, Event},
+    Reader,
+};
+
+use super::ParseError;
+use crate::{CardEntry, GetCardInfo};
+
+/// Errors found while reading a Cockatrice `.cod` decklist. Positions are byte offsets into the document.
+#[derive(Debug)]
+pub enum CodError {
+    MalformedXml {
+        position: u64,
+        error: quick_xml::Error,
+    },
+    NotADeck,
+    MissingAttribute {
+        position: u64,
+        element: &'static str,
+        attribute: &'static str,
+    },
+    NotANumber {
+        card_name: String,
+        string: String,
+        error: ParseIntError,
+    },
+    AmountIsNotPositive {
+        card_name: String,
+        amount: i64,
+    },
+    AmountIsTooLarge {
+        card_name: String,
+        amount: i64,
+    },
+    CouldntParseCard {
+        card_name: String,
+        error: Box<ParseError>,
+    },
+}
+
+impl Display for CodError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MalformedXml { position, error } => {
+                write!(f, "Malformed XML at byte {position}:\n  {error}")
+            }
+            Self::NotADeck => write!(
+                f,
+                "The document doesn't have a `cockatrice_deck` element, so it isn't a Cockatrice deck"
+            ),
+            Self::MissingAttribute {
+                position,
+                element,
+                attribute,
+            } => write!(
+                f,
+                "The `{element}` element at byte {position} is missing its `{attribute}` attribute"
+            ),
+            Self::NotANumber {
+                card_name,
+                string,
+                error,
+            } => write!(
+                f,
+                "Failed to parse the amount of {card_name}, `{string}`, as a number:\n  {error}"
+            ),
+            Self::AmountIsNotPositive { card_name, amount } => write!(
+                f,
+                "Tried to create {card_name} with an amount of {amount}, but amounts must be positive"
+            ),
+            Self::AmountIsTooLarge { card_name, amount } => write!(
+                f,
+                "Tried to create {card_name} with an amount of {amount}, but amounts can be at most {}",
+                u32::MAX
+            ),
+            Self::CouldntParseCard { card_name, error } => {
+                write!(f, "Failed to parse the card `{card_name}`:\n  {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CodError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::MalformedXml { error, .. } => Some(error),
+            Self::NotANumber { error, .. } => Some(error),
+            Self::CouldntParseCard { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// The main zone and the sideboard zone of a decklist, in that order.
+pub type CodDeck<T> = (Vec<CardEntry<T>>, Vec<CardEntry<T>>);
+
+/// Parses the main zone of a Cockatrice `.cod` decklist. Other zones are ignored.
+/// # Errors
+/// Under the same situations as `parse_cod_with_sideboard`.
+pub fn parse_cod<T: GetCardInfo + Clone>(xml: &str) -> Result<Vec<CardEntry<T>>, CodError> {
+    parse_cod_with_sideboard(xml).map(|(main, _)| main)
+}
+
+/// Parses the main zone and the sideboard zone of a Cockatrice `.cod` decklist. Other zones, like the one for tokens, are ignored.
+/// # Errors
+/// - If the document isn't well-formed XML
+/// - If the document has no `cockatrice_deck` element
+/// - If a `zone` element has no `name`, or a `card` element has no `name` or no `number`
+/// - If a card's `number` isn't a positive number, or is larger than `u32::MAX`
+/// - If `T::parse` fails on a card's name
+pub fn parse_cod_with_sideboard<T: GetCardInfo + Clone>(xml: &str) -> Result<CodDeck<T>, CodError> {
+    let mut reader = Reader::from_str(xml);
+    let mut main = vec![];
+    let mut side = vec![];
+    let mut is_deck = false;
+    let mut zone = None;
+    loop {
+        let position = reader.buffer_position();
+        let event = reader
+            .read_event()
+            .map_err(|error| CodError::MalformedXml {
+                position: reader.error_position(),
+                error,
+            })?;
+        match event {
+            Event::Start(element) | Event::Empty(element)
+                if element.name().as_ref() == b"cockatrice_deck" =>
+            {
+                is_deck = true;
+            }
+            Event::Start(element) if element.name().as_ref() == b"zone" => {
+                zone = Some(attribute(&element, "zone", "name", position)?);
+            }
+            Event::End(element) if element.name().as_ref() == b"zone" => zone = None,
+            Event::Start(element) | Event::Empty(element) if element.name().as_ref() == b"card" => {
+                let cards = match zone.as_deref() {
+                    Some("main") => &mut main,
+                    Some("side") => &mut side,
+                    _ => continue,
+                };
+                cards.push(card_entry(&element, position)?);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    if is_deck {
+        Ok((main, side))
+    } else {
+        Err(CodError::NotADeck)
+    }
+}
+
+/// Writes `deck` as a Cockatrice `.cod` decklist named `deck_name`, with every card in the main zone.
+#[must_use]
+pub fn to_cod<T: GetCardInfo + Clone>(deck: &[CardEntry<T>], deck_name: &str) -> String {
+    // Writing into a `String` never fails
+    let mut cod = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    cod.push_str("<cockatrice_deck version=\"1\">\n");
+    let _ = writeln!(cod, "    <deckname>{}</deckname>", escape(deck_name));
+    cod.push_str("    <comments></comments>\n");
+    cod.push_str("    <zone name=\"main\">\n");
+    for entry in deck {
+        let _ = writeln!(
+            cod,
+            "        <card number=\"{}\" name=\"{}\"/>",
+            entry.amount,
+            escape(entry.card.get_name())
+        );
+    }
+    cod.push_str("    </zone>\n");
+    cod.push_str("</cockatrice_deck>\n");
+    cod
+}
+
+/// Reads the `CardEntry` out of a `card` element.
+fn card_entry<T: GetCardInfo + Clone>(
+    element: &BytesStart,
+    position: u64,
+) -> Result<CardEntry<T>, CodError> {
+    let card_name = attribute(element, "card", "name", position)?;
+    let number = attribute(element, "card", "number", position)?;
+    let amount = match number.trim().parse::<i64>() {
+        Ok(amount) if amount > 0 => match u32::try_from(amount).ok().and_then(NonZeroU32::new) {
+            Some(amount) => amount,
+            None => return Err(CodError::AmountIsTooLarge { card_name, amount }),
+        },
+        Ok(amount) => return Err(CodError::AmountIsNotPositive { card_name, amount }),
+        Err(error) => {
+            return Err(CodError::NotANumber {
+                card_name,
+                string: number,
+                error,
+            })
+        }
+    };
+    match T::parse(&card_name) {
+        Ok(card) => Ok(CardEntry::new(card, amount)),
+        Err(error) => Err(CodError::CouldntParseCard {
+            card_name,
+            error: Box::new(error),
+        }),
+    }
+}
+
+/// The unescaped value of an element's attribute. `element` is the name of the element, used to report errors.
+fn attribute(
+    start: &BytesStart,
+    element: &'static str,
+    attribute: &'static str,
+    position: u64,
+) -> Result<String, CodError> {
+    match start.try_get_attribute(attribute) {
+        Ok(Some(value)) => value
+            .unescape_value()
+            .map(Cow::into_owned)
+            .map_err(|error| CodError::MalformedXml { position, error }),
+        Ok(None) => Err(CodError::MissingAttribute {
+            position,
+            element,
+            attribute,
+        }),
+        Err(error) => Err(CodError::MalformedXml { position, error }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_card::{deck, TestCard};
+
+    const SAMPLE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<cockatrice_deck version="1">
+    <deckname>Burn</deckname>
+    <comments>Goes face</comments>
+    <zone name="main">
+        <card number="4" name="Lightning Bolt"/>
+        <card number="20" name="Mountain"/>
+        <card number="1" name="Fire &amp; Ice"/>
+    </zone>
+    <zone name="side">
+        <card number="2" name="Pyroblast"/>
+    </zone>
+    <zone name="tokens">
+        <card number="1" name="Goblin"/>
+    </zone>
+</cockatrice_deck>
+"#;
+
+    fn names(entries: &[CardEntry<TestCard>]) -> Vec<(&str, u32)> {
+        entries
+            .iter()
+            .map(|entry| (entry.card.get_name(), entry.amount.get()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_the_main_zone_and_the_sideboard() {
+        let (main, side) = parse_cod_with_sideboard::<TestCard>(SAMPLE).unwrap();
+        assert_eq!(
+            names(&main),
+            vec![("Lightning Bolt", 4), ("Mountain", 20), ("Fire & Ice", 1)]
+        );
+        assert_eq!(names(&side), vec![("Pyroblast", 2)]);
+        assert_eq!(names(&parse_cod::<TestCard>(SAMPLE).unwrap()), names(&main));
+    }
+
+    #[test]
+    fn bad_documents_are_errors() {
+        let error = |xml: &str| parse_cod::<TestCard>(xml).map(|_| ()).unwrap_err();
+        assert!(matches!(
+            error("<cockatrice_deck><zone name=\"main\"></cockatrice_deck>"),
+            CodError::MalformedXml { .. }
+        ));
+        assert!(matches!(error("<deck></deck>"), CodError::NotADeck));
+        let deck = |card: &str| {
+            format!("<cockatrice_deck><zone name=\"main\">{card}</zone></cockatrice_deck>")
+        };
+        assert!(matches!(
+            error(&deck("<card name=\"Island\"/>")),
+            CodError::MissingAttribute {
+                attribute: "number",
+                ..
+            }
+        ));
+        assert!(matches!(
+            error(&deck("<card number=\"four\" name=\"Island\"/>")),
+            CodError::NotANumber { .. }
+        ));
+        assert!(matches!(
+            error(&deck("<card number=\"0\" name=\"Island\"/>")),
+            CodError::AmountIsNotPositive { amount: 0, .. }
+        ));
+        assert!(matches!(
+            error(&deck("<card number=\"4294967296\" name=\"Island\"/>")),
+            CodError::AmountIsTooLarge { .. }
+        ));
+        assert!(matches!(
+            error(&deck("<card number=\"1\" name=\"Is!land\"/>")),
+            CodError::CouldntParseCard { .. }
+        ));
+    }
+
+    #[test]
+    fn exporting_then_importing_keeps_the_cards() {
+        let cards = deck(&[
+            ("Lightning Bolt", 4),
+            ("Fire & Ice", 1),
+            ("\"Hans\" <Run>", 2),
+        ]);
+        let cod = to_cod(&cards, "<Burn>");
+        assert!(cod.contains("<deckname>&lt;Burn&gt;</deckname>"));
+        let read = parse_cod::<TestCard>(&cod).unwrap();
+        assert_eq!(names(&read), names(&cards));
+        let main = parse_cod::<TestCard>(SAMPLE).unwrap();
+        let read = parse_cod::<TestCard>(&to_cod(&main, "Burn")).unwrap();
+        assert_eq!(names(&read), names(&main));
+    }
+}
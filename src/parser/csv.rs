@@ -0,0 +1,221 @@
+use std::{num::NonZeroU32, sync::Arc};
+
+use ::csv::{ReaderBuilder, StringRecord, Trim, WriterBuilder};
+
+use super::{
+    add_entry, all_or_nothing, DuplicateStrategy, Error, LinePosition, ParseError, ParseOptions,
+    UsedNames,
+};
+use crate::{CardEntry, GetCardInfo};
+
+/// The order of the columns of a CSV decklist.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CsvColumns {
+    /// Rows look like `4,Lightning Bolt`
+    #[default]
+    AmountName,
+    /// Rows look like `Lightning Bolt,4`
+    NameAmount,
+}
+
+/// Options that change how CSV decklists are read and written.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CsvOptions {
+    pub columns: CsvColumns,
+    /// Whether the first row is a header rather than a card.
+    pub has_header: bool,
+    /// What to do when the same card name appears multiple times.
+    pub duplicate_strategy: DuplicateStrategy,
+}
+
+/// Parses a CSV decklist with one card per row. Names may be quoted, like `4,"Fire, Ice"`, and whitespace around fields is ignored.
+/// # Errors
+/// - If a row doesn't have exactly two fields, or isn't valid CSV
+/// - If an amount isn't a number, is 0 or less, or is larger than `u32::MAX`
+/// - If a name is empty, or `T::parse` fails on it
+/// - If the same card name appears multiple times and `options.duplicate_strategy` is `DuplicateStrategy::Error`
+pub fn parse_csv<T: GetCardInfo + Clone>(
+    input: &str,
+    options: &CsvOptions,
+) -> Result<Vec<CardEntry<T>>, Vec<ParseError>> {
+    let parse_options = ParseOptions {
+        duplicate_strategy: options.duplicate_strategy,
+        ..Default::default()
+    };
+    let mut reader = ReaderBuilder::new()
+        .has_headers(options.has_header)
+        .flexible(true)
+        .trim(Trim::All)
+        .from_reader(input.as_bytes());
+    let mut cards = vec![];
+    let mut used_names = UsedNames::new();
+    let mut errors = vec![];
+    for (idx, record) in reader.records().enumerate() {
+        let line = record
+            .as_ref()
+            .ok()
+            .and_then(StringRecord::position)
+            .and_then(|position| usize::try_from(position.line()).ok())
+            .unwrap_or_else(|| idx + 1 + usize::from(options.has_header));
+        match record
+            .map_err(|error| ParseError::custom(error.to_string()))
+            .and_then(|record| parse_record(&record, options.columns))
+        {
+            Ok(entry) => add_entry(
+                &mut cards,
+                &mut used_names,
+                &mut errors,
+                entry,
+                line,
+                &parse_options,
+            ),
+            Err(error) => errors.push(error.at_line(line)),
+        }
+    }
+    all_or_nothing((cards, errors))
+}
+
+/// Reads the `CardEntry` out of a row.
+fn parse_record<T: GetCardInfo + Clone>(
+    record: &StringRecord,
+    columns: CsvColumns,
+) -> Result<CardEntry<T>, ParseError> {
+    let error = |error| ParseError {
+        position: LinePosition::void(),
+        error,
+        source_line: None,
+    };
+    let (amount, name) = match (columns, record.get(0), record.get(1), record.len()) {
+        (CsvColumns::AmountName, Some(amount), Some(name), 2)
+        | (CsvColumns::NameAmount, Some(name), Some(amount), 2) => (amount, name),
+        (_, _, _, len) => {
+            return Err(ParseError::custom(format!(
+                "Expected a row with an amount and a name, but it has {len} fields"
+            )))
+        }
+    };
+    if name.is_empty() {
+        return Err(error(Error::NameIsEmpty));
+    }
+    let amount = match amount.parse::<i64>() {
+        Ok(0) => {
+            return Err(error(Error::AmountIsZero {
+                card_name: name.to_owned(),
+            }))
+        }
+        Ok(amount) if amount < 0 => {
+            return Err(error(Error::AmountIsNegative {
+                card_name: name.to_owned(),
+                amount,
+            }))
+        }
+        Ok(amount) => match u32::try_from(amount).ok().and_then(NonZeroU32::new) {
+            Some(amount) => amount,
+            None => {
+                return Err(error(Error::AmountIsTooLarge {
+                    card_name: name.to_owned(),
+                    amount,
+                }))
+            }
+        },
+        Err(parse_error) => {
+            return Err(error(Error::NotANumber {
+                string: amount.to_owned(),
+                error: Arc::new(parse_error),
+            }))
+        }
+    };
+    Ok(CardEntry::new(T::parse(name)?, amount))
+}
+
+/// Writes `deck` as a CSV decklist, quoting names when needed.
+#[must_use]
+pub fn to_csv<T: GetCardInfo + Clone>(deck: &[CardEntry<T>], options: &CsvOptions) -> String {
+    // Writing into a `Vec` never fails
+    let mut writer = WriterBuilder::new().from_writer(vec![]);
+    let row = |amount: &str, name: &str| match options.columns {
+        CsvColumns::AmountName => [amount.to_owned(), name.to_owned()],
+        CsvColumns::NameAmount => [name.to_owned(), amount.to_owned()],
+    };
+    if options.has_header {
+        let _ = writer.write_record(row("amount", "name"));
+    }
+    for entry in deck {
+        let _ = writer.write_record(row(&entry.amount.to_string(), entry.card.get_name()));
+    }
+    let bytes = writer.into_inner().unwrap_or_default();
+    String::from_utf8(bytes).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_card::{deck, TestCard};
+
+    fn names(entries: &[CardEntry<TestCard>]) -> Vec<(&str, u32)> {
+        entries
+            .iter()
+            .map(|entry| (entry.card.get_name(), entry.amount.get()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_a_header_and_quoted_names() {
+        let options = CsvOptions {
+            columns: CsvColumns::NameAmount,
+            has_header: true,
+            ..CsvOptions::default()
+        };
+        let input = "name,amount\n\"Fire, Ice\",2\n Island , 20\n";
+        let cards = parse_csv::<TestCard>(input, &options).unwrap();
+        assert_eq!(names(&cards), vec![("Fire, Ice", 2), ("Island", 20)]);
+    }
+
+    #[test]
+    fn parses_rows_without_a_header() {
+        let input = "4,Lightning Bolt\n1,\"Fire, Ice\"\n";
+        let cards = parse_csv::<TestCard>(input, &CsvOptions::default()).unwrap();
+        assert_eq!(names(&cards), vec![("Lightning Bolt", 4), ("Fire, Ice", 1)]);
+    }
+
+    #[test]
+    fn errors_point_at_the_row() {
+        let input = "4,Lightning Bolt\n0,Island\nfour,Mountain\n1,Island,Extra\n-1,Forest\n2,Lightning Bolt\n";
+        let Err(errors) = parse_csv::<TestCard>(input, &CsvOptions::default()) else {
+            panic!("five rows are invalid");
+        };
+        let found: Vec<_> = errors.iter().map(ParseError::line).collect();
+        assert_eq!(found, vec![Some(2), Some(3), Some(4), Some(5), Some(6)]);
+        assert!(matches!(errors[0].error(), Error::AmountIsZero { .. }));
+        assert!(matches!(errors[1].error(), Error::NotANumber { .. }));
+        assert!(matches!(errors[3].error(), Error::AmountIsNegative { .. }));
+        assert!(matches!(errors[4].error(), Error::NameMultipleTimes { .. }));
+        let options = CsvOptions {
+            duplicate_strategy: DuplicateStrategy::Sum,
+            ..CsvOptions::default()
+        };
+        let cards = parse_csv::<TestCard>("1,Island\n2,Island\n", &options).unwrap();
+        assert_eq!(names(&cards), vec![("Island", 3)]);
+    }
+
+    #[test]
+    fn exporting_then_importing_keeps_the_cards() {
+        let cards = deck(&[("Lightning Bolt", 4), ("Fire, Ice", 1), ("\"Hans\"", 2)]);
+        for options in [
+            CsvOptions::default(),
+            CsvOptions {
+                columns: CsvColumns::NameAmount,
+                has_header: true,
+                ..CsvOptions::default()
+            },
+        ] {
+            let csv = to_csv(&cards, &options);
+            let read = parse_csv::<TestCard>(&csv, &options).unwrap();
+            assert_eq!(names(&read), names(&cards));
+        }
+        assert_eq!(
+            to_csv(&deck(&[("Fire, Ice", 1)]), &CsvOptions::default()),
+            "1,\"Fire, Ice\"\n"
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use super::{
+    add_entry, parse_line_with_options, DuplicateStrategy, ParseError, ParseOptions, UsedNames,
+    BYTE_ORDER_MARK,
+};
+use crate::{CardEntry, GetCardInfo};
+
+/// A card from a Forge `.dck` decklist, along with the set the line specified, if any.
+#[derive(Clone)]
+pub struct DckEntry<T> {
+    pub entry: CardEntry<T>,
+    /// The set code, like `2XM` in `4 Lightning Bolt|2XM`
+    pub set: Option<String>,
+}
+
+/// The main section and the sideboard section of a decklist, in that order.
+pub type DckDeck<T> = (Vec<DckEntry<T>>, Vec<DckEntry<T>>);
+
+/// The sections of a Forge decklist.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Main,
+    Sideboard,
+    /// Sections whose lines aren't cards of the deck, like `[metadata]`, or that aren't supported
+    Ignored,
+}
+
+/// The cards of one section, along with the set code of each of them. Cards named more than once keep the set of the line whose entry was kept, which is the last one with `DuplicateStrategy::KeepLast` and the first one otherwise.
+struct SectionEntries<T> {
+    cards: Vec<CardEntry<T>>,
+    used_names: UsedNames,
+    sets: Vec<Option<String>>,
+}
+
+impl<T: GetCardInfo + Clone> SectionEntries<T> {
+    fn new() -> Self {
+        Self {
+            cards: vec![],
+            used_names: UsedNames::new(),
+            sets: vec![],
+        }
+    }
+
+    fn into_entries(self) -> Vec<DckEntry<T>> {
+        self.cards
+            .into_iter()
+            .zip(self.sets)
+            .map(|(entry, set)| DckEntry { entry, set })
+            .collect()
+    }
+}
+
+/// Parses a Forge `.dck` decklist using the default `ParseOptions`.
+/// # Errors
+/// Under the same situations as `parse_dck_with_options`.
+pub fn parse_dck<T: GetCardInfo + Clone>(input: &str) -> Result<DckDeck<T>, Vec<ParseError>> {
+    parse_dck_with_options(input, &ParseOptions::default())
+}
+
+/// Parses a Forge `.dck` decklist, returning its main section and its sideboard section.
+///
+/// Sections start with headers like `[Main]` or `[Sideboard]`, matched ignoring case, and lines before the first header belong to the main section. Other sections, like `[metadata]` or `[Commander]`, are ignored. Lines look like `4 Lightning Bolt|2XM`, where the set code is optional and anything after a second `|`, like the art index, is ignored. Cards named more than once in the same section are handled according to `options.duplicate_strategy`.
+/// # Errors
+/// - If `parse_line_with_options` fails on any of the lines of the main section or the sideboard section, once the set code is removed
+/// - If a card is named more than once in the same section and `options.duplicate_strategy` is `DuplicateStrategy::Error`
+pub fn parse_dck_with_options<T: GetCardInfo + Clone>(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<DckDeck<T>, Vec<ParseError>> {
+    let mut main = SectionEntries::new();
+    let mut sideboard = SectionEntries::new();
+    let mut errors = vec![];
+    let mut section = Section::Main;
+    let input = input.strip_prefix(BYTE_ORDER_MARK).unwrap_or(input);
+    for (line_idx, line) in (1..).zip(input.lines()) {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(header) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            section = match header.trim().to_lowercase().as_str() {
+                "main" => Section::Main,
+                "sideboard" => Section::Sideboard,
+                _ => Section::Ignored,
+            };
+            continue;
+        }
+        let (card, set) = split_set(line);
+        let entries = match section {
+            Section::Main => &mut main,
+            Section::Sideboard => &mut sideboard,
+            Section::Ignored => continue,
+        };
+        match parse_line_with_options::<T>(card, options) {
+            Ok(entry) => {
+                let key = options.name_key(entry.card.get_name());
+                let duplicate = entries.used_names.get(&key).copied();
+                add_entry(
+                    &mut entries.cards,
+                    &mut entries.used_names,
+                    &mut errors,
+                    entry,
+                    line_idx,
+                    options,
+                );
+                match duplicate {
+                    None => entries.sets.push(set),
+                    Some(idx) if options.duplicate_strategy == DuplicateStrategy::KeepLast => {
+                        entries.sets[idx] = set;
+                    }
+                    Some(_) => {}
+                }
+            }
+            Err(error) => errors.push(error.at_line(line_idx).with_source_line(line)),
+        }
+    }
+    if errors.is_empty() {
+        Ok((main.into_entries(), sideboard.into_entries()))
+    } else {
+        Err(errors)
+    }
+}
+
+/// Splits the `|SET` suffix of a line from the rest of it, dropping anything after the set code.
+fn split_set(line: &str) -> (&str, Option<String>) {
+    let Some((card, printing)) = line.split_once('|') else {
+        return (line, None);
+    };
+    let set = printing.split('|').next().unwrap_or_default().trim();
+    (card.trim_end(), (!set.is_empty()).then(|| set.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_card::TestCard;
+
+    fn names(entries: &[DckEntry<TestCard>]) -> Vec<(&str, u32)> {
+        entries
+            .iter()
+            .map(|entry| (entry.entry.card.get_name(), entry.entry.amount.get()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_sections_and_sets() {
+        let input = "[metadata]\nName=Burn\n[Main]\n4 Lightning Bolt|2XM|1\n20 Mountain\n[sideboard]\n2 Pyroblast|ICE\n[Commander]\n1 Ignored\n";
+        let (main, sideboard) = parse_dck::<TestCard>(input).unwrap();
+        assert_eq!(names(&main), vec![("Lightning Bolt", 4), ("Mountain", 20)]);
+        assert_eq!(main[0].set.as_deref(), Some("2XM"));
+        assert_eq!(main[1].set, None);
+        assert_eq!(names(&sideboard), vec![("Pyroblast", 2)]);
+        assert_eq!(sideboard[0].set.as_deref(), Some("ICE"));
+    }
+
+    #[test]
+    fn lines_before_any_header_are_in_the_main_section() {
+        let (main, sideboard) = parse_dck::<TestCard>("1 Island\n[Sideboard]\n1 Bolt\n").unwrap();
+        assert_eq!(names(&main), vec![("Island", 1)]);
+        assert_eq!(names(&sideboard), vec![("Bolt", 1)]);
+    }
+
+    #[test]
+    fn duplicates_follow_the_duplicate_strategy() {
+        let input = "[Main]\n2 Island|M21\n3 Island|ZNR\n";
+        assert!(parse_dck::<TestCard>(input).is_err());
+        let options = ParseOptions {
+            duplicate_strategy: DuplicateStrategy::Sum,
+            ..ParseOptions::default()
+        };
+        let (main, _) = parse_dck_with_options::<TestCard>(input, &options).unwrap();
+        assert_eq!(names(&main), vec![("Island", 5)]);
+        assert_eq!(main[0].set.as_deref(), Some("M21"));
+        let options = ParseOptions {
+            duplicate_strategy: DuplicateStrategy::KeepLast,
+            ..ParseOptions::default()
+        };
+        let (main, _) = parse_dck_with_options::<TestCard>(input, &options).unwrap();
+        assert_eq!(names(&main), vec![("Island", 3)]);
+        assert_eq!(main[0].set.as_deref(), Some("ZNR"));
+    }
+
+    #[test]
+    fn the_same_card_can_be_in_both_sections() {
+        let (main, sideboard) = parse_dck::<TestCard>("1 Island\n[Sideboard]\n1 Island\n").unwrap();
+        assert_eq!(names(&main), vec![("Island", 1)]);
+        assert_eq!(names(&sideboard), vec![("Island", 1)]);
+    }
+
+    #[test]
+    fn errors_point_at_the_line() {
+        let Err(errors) = parse_dck::<TestCard>("[Main]\n4 Bolt\nfour Bolt\n") else {
+            panic!("the third line isn't a card");
+        };
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line(), Some(3));
+    }
+}
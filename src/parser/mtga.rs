@@ -0,0 +1,229 @@
+use super::{
+    add_entry, parse_line_with_options, DuplicateStrategy, ParseError, ParseOptions, UsedNames,
+    BYTE_ORDER_MARK,
+};
+use crate::{CardEntry, GetCardInfo};
+
+/// A card from a Magic: The Gathering Arena decklist, along with the printing the line specified, if any.
+#[derive(Clone)]
+pub struct MtgaEntry<T> {
+    pub entry: CardEntry<T>,
+    /// The set code, like `2XM` in `4 Lightning Bolt (2XM) 129`
+    pub set: Option<String>,
+    /// The collector number, like `129` in `4 Lightning Bolt (2XM) 129`
+    pub collector_number: Option<String>,
+}
+
+/// The maindeck and the sideboard of a decklist, in that order.
+pub type MtgaDeck<T> = (Vec<MtgaEntry<T>>, Vec<MtgaEntry<T>>);
+
+/// The sections of a Magic: The Gathering Arena decklist.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Maindeck,
+    Sideboard,
+}
+
+/// The cards of one section, along with the set code and the collector number of each of them. Cards named more than once keep the printing of the line whose entry was kept, which is the last one with `DuplicateStrategy::KeepLast` and the first one otherwise.
+struct SectionEntries<T> {
+    cards: Vec<CardEntry<T>>,
+    used_names: UsedNames,
+    printings: Vec<(Option<String>, Option<String>)>,
+}
+
+impl<T: GetCardInfo + Clone> SectionEntries<T> {
+    fn new() -> Self {
+        Self {
+            cards: vec![],
+            used_names: UsedNames::new(),
+            printings: vec![],
+        }
+    }
+
+    fn into_entries(self) -> Vec<MtgaEntry<T>> {
+        self.cards
+            .into_iter()
+            .zip(self.printings)
+            .map(|(entry, (set, collector_number))| MtgaEntry {
+                entry,
+                set,
+                collector_number,
+            })
+            .collect()
+    }
+}
+
+/// Parses a decklist exported by Magic: The Gathering Arena using the default `ParseOptions`.
+/// # Errors
+/// Under the same situations as `parse_mtga_with_options`.
+pub fn parse_mtga<T: GetCardInfo + Clone>(input: &str) -> Result<MtgaDeck<T>, Vec<ParseError>> {
+    parse_mtga_with_options(input, &ParseOptions::default())
+}
+
+/// Parses a decklist exported by Magic: The Gathering Arena, returning its maindeck and its sideboard.
+///
+/// Lines look like `4 Lightning Bolt (2XM) 129`, where the set code and the collector number are optional. The sideboard starts after a `Sideboard` header, or after the first blank line that follows a card of the maindeck. A `Deck` header before the maindeck is ignored. Cards named more than once in the same section are handled according to `options.duplicate_strategy`.
+/// # Errors
+/// - If `parse_line_with_options` fails on any of the lines, once the set code and the collector number are removed
+/// - If a card is named more than once in the same section and `options.duplicate_strategy` is `DuplicateStrategy::Error`
+pub fn parse_mtga_with_options<T: GetCardInfo + Clone>(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<MtgaDeck<T>, Vec<ParseError>> {
+    let mut maindeck = SectionEntries::new();
+    let mut sideboard = SectionEntries::new();
+    let mut errors = vec![];
+    let mut section = Section::Maindeck;
+    let input = input.strip_prefix(BYTE_ORDER_MARK).unwrap_or(input);
+    for (line_idx, line) in (1..).zip(input.lines()) {
+        let line = line.trim();
+        if line.is_empty() {
+            if !maindeck.cards.is_empty() {
+                section = Section::Sideboard;
+            }
+            continue;
+        }
+        if line.eq_ignore_ascii_case("deck") {
+            section = Section::Maindeck;
+            continue;
+        }
+        if line.eq_ignore_ascii_case("sideboard") {
+            section = Section::Sideboard;
+            continue;
+        }
+        let (card, set, collector_number) = split_printing(line);
+        match parse_line_with_options::<T>(card, options) {
+            Ok(entry) => {
+                let entries = match section {
+                    Section::Maindeck => &mut maindeck,
+                    Section::Sideboard => &mut sideboard,
+                };
+                let key = options.name_key(entry.card.get_name());
+                let duplicate = entries.used_names.get(&key).copied();
+                add_entry(
+                    &mut entries.cards,
+                    &mut entries.used_names,
+                    &mut errors,
+                    entry,
+                    line_idx,
+                    options,
+                );
+                match duplicate {
+                    None => entries.printings.push((set, collector_number)),
+                    Some(idx) if options.duplicate_strategy == DuplicateStrategy::KeepLast => {
+                        entries.printings[idx] = (set, collector_number);
+                    }
+                    Some(_) => {}
+                }
+            }
+            Err(error) => errors.push(error.at_line(line_idx).with_source_line(line)),
+        }
+    }
+    if errors.is_empty() {
+        Ok((maindeck.into_entries(), sideboard.into_entries()))
+    } else {
+        Err(errors)
+    }
+}
+
+/// Splits the trailing `(SET) NUM` of a line from the rest of it. The collector number may be missing, but the set code must be enclosed in parentheses and can't have spaces.
+fn split_printing(line: &str) -> (&str, Option<String>, Option<String>) {
+    let Some(open) = line.rfind(" (") else {
+        return (line, None, None);
+    };
+    let printing = &line[open + 2..];
+    let Some(close) = printing.find(')') else {
+        return (line, None, None);
+    };
+    let set = &printing[..close];
+    let collector_number = printing[close + 1..].trim();
+    if set.is_empty()
+        || set.contains(char::is_whitespace)
+        || collector_number.contains(char::is_whitespace)
+    {
+        return (line, None, None);
+    }
+    let collector_number = (!collector_number.is_empty()).then(|| collector_number.to_owned());
+    (&line[..open], Some(set.to_owned()), collector_number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_card::TestCard;
+
+    fn names(entries: &[MtgaEntry<TestCard>]) -> Vec<(&str, u32)> {
+        entries
+            .iter()
+            .map(|entry| (entry.entry.card.get_name(), entry.entry.amount.get()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_printings_and_the_sideboard() {
+        let input = "Deck\n4 Lightning Bolt (2XM) 129\n20 Mountain\n\n2 Pyroblast (ICE)\n";
+        let (maindeck, sideboard) = parse_mtga::<TestCard>(input).unwrap();
+        assert_eq!(
+            names(&maindeck),
+            vec![("Lightning Bolt", 4), ("Mountain", 20)]
+        );
+        assert_eq!(maindeck[0].set.as_deref(), Some("2XM"));
+        assert_eq!(maindeck[0].collector_number.as_deref(), Some("129"));
+        assert_eq!(maindeck[1].set, None);
+        assert_eq!(names(&sideboard), vec![("Pyroblast", 2)]);
+        assert_eq!(sideboard[0].set.as_deref(), Some("ICE"));
+        assert_eq!(sideboard[0].collector_number, None);
+    }
+
+    #[test]
+    fn the_sideboard_header_splits_the_sections() {
+        let input = "1 Island\nSideboard\n1 Negate (RIX) 44\n";
+        let (maindeck, sideboard) = parse_mtga::<TestCard>(input).unwrap();
+        assert_eq!(names(&maindeck), vec![("Island", 1)]);
+        assert_eq!(names(&sideboard), vec![("Negate", 1)]);
+        let Err(errors) = parse_mtga::<TestCard>("1 Island\nfour Negate (RIX) 44\n") else {
+            panic!("`four` isn't an amount");
+        };
+        assert_eq!(errors[0].line(), Some(2));
+    }
+
+    #[test]
+    fn duplicates_follow_the_duplicate_strategy() {
+        let input = "2 Island (M21) 1\n3 Island (ZNR) 2\n";
+        assert!(parse_mtga::<TestCard>(input).is_err());
+        let options = ParseOptions {
+            duplicate_strategy: DuplicateStrategy::Sum,
+            ..ParseOptions::default()
+        };
+        let (maindeck, _) = parse_mtga_with_options::<TestCard>(input, &options).unwrap();
+        assert_eq!(names(&maindeck), vec![("Island", 5)]);
+        assert_eq!(maindeck[0].set.as_deref(), Some("M21"));
+        let options = ParseOptions {
+            duplicate_strategy: DuplicateStrategy::KeepLast,
+            ..ParseOptions::default()
+        };
+        let (maindeck, _) = parse_mtga_with_options::<TestCard>(input, &options).unwrap();
+        assert_eq!(names(&maindeck), vec![("Island", 3)]);
+        assert_eq!(maindeck[0].set.as_deref(), Some("ZNR"));
+    }
+
+    #[test]
+    fn case_insensitive_names_count_as_duplicates() {
+        let options = ParseOptions {
+            duplicate_strategy: DuplicateStrategy::Sum,
+            case_insensitive_names: true,
+            ..ParseOptions::default()
+        };
+        let (maindeck, _) =
+            parse_mtga_with_options::<TestCard>("1 Island\n1 island\n", &options).unwrap();
+        assert_eq!(names(&maindeck), vec![("Island", 2)]);
+    }
+
+    #[test]
+    fn the_same_card_can_be_in_both_sections() {
+        let (maindeck, sideboard) =
+            parse_mtga::<TestCard>("1 Island\nSideboard\n1 Island\n").unwrap();
+        assert_eq!(names(&maindeck), vec![("Island", 1)]);
+        assert_eq!(names(&sideboard), vec![("Island", 1)]);
+    }
+}
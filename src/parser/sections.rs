@@ -0,0 +1,269 @@
+use std::{collections::HashMap, io::BufRead, path::PathBuf, sync::Arc};
+
+#[cfg(feature = "fs")]
+use std::io::BufReader;
+
+#[cfg(feature = "fs")]
+use super::open_file;
+use super::{
+    add_entry, is_blank_or_comment, parse_line_with_options, read_joined_line, Error, LinePosition,
+    ParseError, ParseOptions, UsedNames, BYTE_ORDER_MARK,
+};
+use crate::{CardEntry, GetCardInfo};
+
+/// The section cards go into when they come before any header, or after a `Deck`, `Main` or `Maindeck` header.
+pub const MAIN_SECTION: &str = "main";
+
+/// Headers that start `MAIN_SECTION` instead of a section with their own name.
+const MAIN_SECTION_ALIASES: [&str; 3] = ["deck", "main", "maindeck"];
+
+/// The cards of each section of a decklist, by the section's name.
+pub type Sections<T> = HashMap<String, Vec<CardEntry<T>>>;
+
+/// Options that change how decklists with sections are parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SectionOptions {
+    /// The lines that start a section, like `Sideboard`. They're matched ignoring case, with or without a trailing `:`.
+    pub headers: Vec<String>,
+    /// Whether any line ending in `:` that doesn't start with a digit, like `Tokens:`, starts a section too.
+    pub colon_headers: bool,
+    /// How the cards in each section are parsed. Duplicate names are only looked for within the same section.
+    pub parse_options: ParseOptions,
+}
+
+impl Default for SectionOptions {
+    fn default() -> Self {
+        Self {
+            headers: [
+                "Deck",
+                "Main",
+                "Maindeck",
+                "Sideboard",
+                "Commander",
+                "Companion",
+                "Maybeboard",
+            ]
+            .into_iter()
+            .map(str::to_owned)
+            .collect(),
+            colon_headers: true,
+            parse_options: ParseOptions::default(),
+        }
+    }
+}
+
+impl SectionOptions {
+    /// The name of the section `line` starts, if it's a header. Section names are the header in lowercase, without the trailing `:`, except for `Deck`, `Main` and `Maindeck`, which start `MAIN_SECTION`.
+    fn section_name(&self, line: &str) -> Option<String> {
+        let line = line.trim();
+        let name = line.strip_suffix(':').unwrap_or(line).trim_end();
+        let is_header = self
+            .headers
+            .iter()
+            .any(|header| header.eq_ignore_ascii_case(name))
+            || (self.colon_headers
+                && line.ends_with(':')
+                && !name.is_empty()
+                && !line.starts_with(|chr: char| chr.is_ascii_digit()));
+        if !is_header {
+            return None;
+        }
+        let name = name.to_lowercase();
+        if MAIN_SECTION_ALIASES.contains(&name.as_str()) {
+            Some(MAIN_SECTION.to_owned())
+        } else {
+            Some(name)
+        }
+    }
+}
+
+/// Parses a decklist with sections using the default `SectionOptions`.
+/// # Errors
+/// Under the same situations as `parse_sections_with_options`.
+pub fn parse_sections<T: GetCardInfo + Clone>(input: &str) -> Result<Sections<T>, Vec<ParseError>> {
+    parse_sections_with_options(input, &SectionOptions::default())
+}
+
+/// Parses a decklist split into sections by header lines, like `Sideboard` or `Commander:`, returning the cards of each section.
+///
+/// Cards before the first header, or after a `Deck`, `Main` or `Maindeck` header, go into `MAIN_SECTION`. Blank lines and lines whose first non-whitespace character is `#` are skipped.
+/// # Errors
+/// - If `parse_line_with_options` fails on any of the lines that aren't headers
+/// - If the same card name appears multiple times in a section and `options.parse_options.duplicate_strategy` is `DuplicateStrategy::Error`
+pub fn parse_sections_with_options<T: GetCardInfo + Clone>(
+    input: &str,
+    options: &SectionOptions,
+) -> Result<Sections<T>, Vec<ParseError>> {
+    parse_section_lines(input.as_bytes(), None, options)
+}
+
+/// Parses a file with sections using the default `SectionOptions`.
+/// # Errors
+/// Under the same situations as `parse_file_with_sections_with_options`.
+#[cfg(feature = "fs")]
+pub fn parse_file_with_sections<T: GetCardInfo + Clone>(
+    path: &PathBuf,
+) -> Result<Sections<T>, Vec<ParseError>> {
+    parse_file_with_sections_with_options(path, &SectionOptions::default())
+}
+
+/// Parses a file like `parse_sections_with_options`.
+/// # Errors
+/// - Under the same situations as `parse_sections_with_options`
+/// - If the file can't be opened or a line can't be read
+#[cfg(feature = "fs")]
+pub fn parse_file_with_sections_with_options<T: GetCardInfo + Clone>(
+    path: &PathBuf,
+    options: &SectionOptions,
+) -> Result<Sections<T>, Vec<ParseError>> {
+    let file = open_file(path).map_err(|error| vec![error])?;
+    parse_section_lines(BufReader::new(file), Some(path), options)
+}
+
+/// Parses every line in `reader`, putting the cards in the section of the last header. `path` is only used to report errors.
+fn parse_section_lines<T: GetCardInfo + Clone, R: BufRead>(
+    mut reader: R,
+    path: Option<&PathBuf>,
+    options: &SectionOptions,
+) -> Result<Sections<T>, Vec<ParseError>> {
+    let mut sections: HashMap<String, (Vec<CardEntry<T>>, UsedNames)> = HashMap::new();
+    let mut section = MAIN_SECTION.to_owned();
+    let mut next_line_idx = 1;
+    let mut errors = vec![];
+    loop {
+        let line_idx = next_line_idx;
+        let mut line = String::new();
+        let read = read_joined_line(&mut reader, &mut line);
+        if line_idx == 1 && line.starts_with(BYTE_ORDER_MARK) {
+            line.remove(0);
+        }
+        next_line_idx += read.as_ref().map_or(1, |lines| *lines);
+        match read {
+            Ok(0) => break,
+            Ok(_) if is_blank_or_comment(&line) => {}
+            Ok(_) => {
+                if let Some(name) = options.section_name(&line) {
+                    section = name;
+                    continue;
+                }
+                match parse_line_with_options::<T>(&line, &options.parse_options) {
+                    Ok(entry) => {
+                        let (cards, used_names) = sections.entry(section.clone()).or_default();
+                        add_entry(
+                            cards,
+                            used_names,
+                            &mut errors,
+                            entry,
+                            line_idx,
+                            &options.parse_options,
+                        );
+                    }
+                    Err(error) => errors.push(error.at_line(line_idx).with_source_line(&line)),
+                }
+            }
+            Err(error) => errors.push(ParseError {
+                position: LinePosition {
+                    line: Some(line_idx),
+                    column: None,
+                },
+                error: Error::CouldntReadLine {
+                    path: path.cloned(),
+                    line: line_idx,
+                    error: Arc::new(error),
+                },
+                source_line: None,
+            }),
+        }
+    }
+    if errors.is_empty() {
+        Ok(sections
+            .into_iter()
+            .map(|(name, (cards, _))| (name, cards))
+            .collect())
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parser::DuplicateStrategy, test_card::TestCard};
+
+    fn names(cards: &[CardEntry<TestCard>]) -> Vec<(&str, u32)> {
+        cards
+            .iter()
+            .map(|entry| (entry.card.get_name(), entry.amount.get()))
+            .collect()
+    }
+
+    #[test]
+    fn cards_go_into_the_section_of_the_last_header() {
+        let sections =
+            parse_sections::<TestCard>("4 Bolt\nSideboard\n2 Pyroblast\nTokens:\n1 Goblin\n")
+                .unwrap();
+        assert_eq!(sections.len(), 3);
+        assert_eq!(names(&sections[MAIN_SECTION]), vec![("Bolt", 4)]);
+        assert_eq!(names(&sections["sideboard"]), vec![("Pyroblast", 2)]);
+        assert_eq!(names(&sections["tokens"]), vec![("Goblin", 1)]);
+    }
+
+    #[test]
+    fn main_aliases_start_the_main_section() {
+        for header in ["Deck", "Main", "MAINDECK", "deck:"] {
+            let input = format!("Sideboard\n1 Pyroblast\n{header}\n4 Bolt\n");
+            let sections = parse_sections::<TestCard>(&input).unwrap();
+            assert_eq!(
+                names(&sections[MAIN_SECTION]),
+                vec![("Bolt", 4)],
+                "{header}"
+            );
+            assert_eq!(sections.len(), 2, "{header}");
+        }
+    }
+
+    #[test]
+    fn duplicates_are_only_looked_for_within_a_section() {
+        let sections = parse_sections::<TestCard>("1 Island\nSideboard\n1 Island\n").unwrap();
+        assert_eq!(names(&sections[MAIN_SECTION]), vec![("Island", 1)]);
+        assert_eq!(names(&sections["sideboard"]), vec![("Island", 1)]);
+        assert!(parse_sections::<TestCard>("1 Island\nDeck\n1 Island\n").is_err());
+        let options = SectionOptions {
+            parse_options: ParseOptions {
+                duplicate_strategy: DuplicateStrategy::Sum,
+                ..ParseOptions::default()
+            },
+            ..SectionOptions::default()
+        };
+        let sections =
+            parse_sections_with_options::<TestCard>("1 Island\nMain\n2 Island\n", &options)
+                .unwrap();
+        assert_eq!(names(&sections[MAIN_SECTION]), vec![("Island", 3)]);
+    }
+
+    #[test]
+    fn amounts_ending_in_a_colon_arent_headers() {
+        let sections = parse_sections::<TestCard>("4 Bolt\n").unwrap();
+        assert_eq!(names(&sections[MAIN_SECTION]), vec![("Bolt", 4)]);
+        assert!(parse_sections::<TestCard>("4:\n").is_err());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn parse_file_with_sections_reads_every_section() {
+        let path = crate::test_card::temp_dir("sections").join("deck.txt");
+        std::fs::write(
+            &path,
+            "4 Bolt\n20 Mountain\n\nSideboard\n2 Pyroblast\n\nCommander\n1 Krenko\n",
+        )
+        .unwrap();
+        let sections = parse_file_with_sections::<TestCard>(&path).unwrap();
+        assert_eq!(sections.len(), 3);
+        assert_eq!(
+            names(&sections[MAIN_SECTION]),
+            vec![("Bolt", 4), ("Mountain", 20)]
+        );
+        assert_eq!(names(&sections["sideboard"]), vec![("Pyroblast", 2)]);
+        assert_eq!(names(&sections["commander"]), vec![("Krenko", 1)]);
+    }
+}
@@ -0,0 +1,200 @@
+use std::cell::Cell;
+
+use crate::{parser::ParseError, tts::CardShape, CardEntry, CardError, GetCardInfo};
+
+/// A card for tests, whose front image is made from its name. Cards named `NoBack` don't have a back image, and names containing `!` can't be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestCard {
+    name: String,
+}
+
+impl TestCard {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+        }
+    }
+}
+
+impl GetCardInfo for TestCard {
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_front_image(&self) -> Result<String, CardError> {
+        Ok(format!("https://example.com/{}.png", self.name))
+    }
+
+    fn get_back_image(&self) -> Result<String, CardError> {
+        if self.name == "NoBack" {
+            return Err(CardError::BackImageFileError {
+                card_name: self.name.clone(),
+                image_url: String::new(),
+            });
+        }
+        Ok(crate::PLACEHOLDER_BACK_IMAGE.to_owned())
+    }
+
+    fn parse(string: &str) -> Result<Self, ParseError> {
+        if string.contains('!') {
+            return Err(ParseError::custom(format!("{string} isn't a card")));
+        }
+        Ok(Self::new(string))
+    }
+}
+
+thread_local! {
+    static RESOLVE_CALLS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A card for tests whose front image is only known after `GetCardInfo::resolve_batch`, which counts how many times it was called on the current thread. The image points at a port nothing listens on, so downloading it fails right away.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolvingCard {
+    name: String,
+    front_image: Option<String>,
+}
+
+impl ResolvingCard {
+    pub fn deck(names: &[&str]) -> Vec<CardEntry<Self>> {
+        names
+            .iter()
+            .map(|name| {
+                let card = Self {
+                    name: (*name).to_owned(),
+                    front_image: None,
+                };
+                CardEntry::try_new(card, 2).unwrap()
+            })
+            .collect()
+    }
+
+    pub fn resolve_calls() -> usize {
+        RESOLVE_CALLS.get()
+    }
+}
+
+impl GetCardInfo for ResolvingCard {
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_front_image(&self) -> Result<String, CardError> {
+        self.front_image
+            .clone()
+            .ok_or_else(|| CardError::custom(format!("{} wasn't resolved", self.name)))
+    }
+
+    fn resolve_batch(cards: &mut [Self]) -> Result<(), CardError> {
+        RESOLVE_CALLS.set(RESOLVE_CALLS.get() + 1);
+        for card in cards {
+            card.front_image = Some(format!("http://127.0.0.1:9/{}.png", card.name));
+        }
+        Ok(())
+    }
+
+    fn parse(string: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            name: string.to_owned(),
+            front_image: None,
+        })
+    }
+}
+
+/// An empty directory for a test to write files into, named after the test.
+#[cfg(feature = "fs")]
+pub fn temp_dir(test: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("shrek-deck-{}-{test}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// A deck with `amount` copies of each card in `names`.
+pub fn deck(names: &[(&str, u32)]) -> Vec<CardEntry<TestCard>> {
+    names
+        .iter()
+        .map(|&(name, amount)| CardEntry::try_new(TestCard::new(name), amount.into()).unwrap())
+        .collect()
+}
+
+/// A card for tests that overrides the optional methods of `GetCardInfo` with the values of its fields.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CustomCard {
+    pub name: String,
+    pub description: Option<String>,
+    pub sideways: bool,
+    pub scale: f64,
+    pub unique_back: bool,
+}
+
+impl CustomCard {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            description: None,
+            sideways: false,
+            scale: 1.0,
+            unique_back: false,
+        }
+    }
+
+    pub fn entry(self, amount: u32) -> CardEntry<Self> {
+        CardEntry::try_new(self, amount.into()).unwrap()
+    }
+}
+
+impl GetCardInfo for CustomCard {
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_front_image(&self) -> Result<String, CardError> {
+        Ok(format!("https://example.com/{}.png", self.name))
+    }
+
+    fn get_back_image(&self) -> Result<String, CardError> {
+        Ok(format!("https://example.com/{}-back.png", self.name))
+    }
+
+    fn get_card_shape(&self) -> Result<CardShape, CardError> {
+        Ok(CardShape::Rectangle)
+    }
+
+    fn get_description(&self) -> Option<String> {
+        self.description.clone()
+    }
+
+    fn is_sideways(&self) -> bool {
+        self.sideways
+    }
+
+    fn get_scale(&self) -> f64 {
+        self.scale
+    }
+
+    fn has_unique_back(&self) -> bool {
+        self.unique_back
+    }
+
+    fn parse(string: &str) -> Result<Self, ParseError> {
+        Ok(Self::new(string))
+    }
+}
+
+/// Runs `f` with `HOME` pointing at an empty directory named after the test, so that Tabletop Simulator's directories are made inside of it. Tests that change `HOME` run one at a time.
+#[cfg(all(feature = "fs", target_os = "linux"))]
+pub fn with_temp_home<R>(test: &str, f: impl FnOnce(&std::path::Path) -> R) -> R {
+    static HOME: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    let _guard = HOME
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let home = temp_dir(test);
+    let old_home = std::env::var_os("HOME");
+    std::env::set_var("HOME", &home);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| f(&home)));
+    match old_home {
+        Some(old_home) => std::env::set_var("HOME", old_home),
+        None => std::env::remove_var("HOME"),
+    }
+    result.unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+}
@@ -1,15 +1,29 @@
 #![warn(clippy::pedantic)]
 #![warn(clippy::nursery)]
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashSet},
     fmt::Display,
     io,
-    path::{Path, PathBuf},
+    num::NonZeroU32,
+    path::PathBuf,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+#[cfg(any(
+    feature = "fs",
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "linux"
+))]
+use std::path::Path;
+use uuid::Uuid;
 
-use crate::{generate_guid, CardEntry, CardError, GetCardInfo};
+#[cfg(feature = "async")]
+use crate::GetCardInfoAsync;
+use crate::{resolve_deck, CardEntry, CardError, GetCardInfo};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
@@ -27,68 +41,437 @@ pub struct SaveState {
     table: String,
     sky: String,
     note: String,
-    tab_states: HashMap<String, String>,
+    tab_states: BTreeMap<String, String>,
     lua_script: String,
     lua_script_state: String,
     #[serde(rename = "XmlUI")]
     xml_ui: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    snap_points: Vec<SnapPoint>,
     object_states: Vec<ObjectState>,
+    /// Fields this implementation doesn't know about, kept so that they aren't lost when reading and writing back a save.
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+}
+
+impl Default for SaveState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SaveState {
+    /// A save without any objects, to add objects to with `with_object`, or to use as the settings of a `DeckWriter`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::from_object_states(vec![])
+    }
+
     /// Takes a vector of `CardEntry` and provides a `SaveState` for that deck. All saved objects in Tabletop Simulator are `SaveStates`.
     /// # Errors
     /// Under any situation that the `GetCardInfo` implementations of the provided type would error.
     pub fn new_with_deck<T: GetCardInfo + Clone>(
         deck: Vec<CardEntry<T>>,
     ) -> Result<Self, CardError> {
-        let (deck_ids, custom_deck, contained_objects) = generate_deck_data(deck)?;
-        let (deck_ids, contained_objects) = (Some(deck_ids), Some(contained_objects));
-        let object_state = ObjectState {
-            guid: generate_guid(),
-            name: "Deck".to_string(),
-            transform: TransformState {
-                rot_y: 180.0,
-                ..Default::default()
-            },
-            nickname: String::new(),
-            description: String::new(),
-            gm_notes: String::new(),
-            alt_look_angle: Vector3::default(),
-            color_difuse: ColourState {
-                r: 0.713_235_259,
-                g: 0.713_235_259,
-                b: 0.713_235_259,
-            },
-            layout_group_sort_index: 0,
-            value: 0,
-            locked: false,
-            grid: true,
-            snap: true,
-            ignore_fow: false,
-            measure_movement: false,
-            drag_selectable: true,
-            autoraise: true,
-            sticky: true,
-            tooltip: true,
-            grid_projection: false,
-            hide_when_face_down: true,
-            hands: false,
-            card_id: None,
-            sideways_card: false,
-            deck_ids,
-            custom_deck,
-            lua_script: String::new(),
-            lua_script_state: String::new(),
-            xml_ui: String::new(),
-            contained_objects,
+        Self::new_with_deck_and_limits(deck, &DeckLimits::default())
+    }
+
+    /// Like `new_with_deck`, but with custom limits on the amount of cards in the deck.
+    /// # Errors
+    /// - Under any situation that the `GetCardInfo` implementations of the provided type would error.
+    /// - If the deck has more cards than `limits` allows.
+    pub fn new_with_deck_and_limits<T: GetCardInfo + Clone>(
+        deck: Vec<CardEntry<T>>,
+        limits: &DeckLimits,
+    ) -> Result<Self, CardError> {
+        let mut guids = GuidStrategy::Random.generator();
+        let deck_data = generate_deck_data(deck, limits, None, &mut guids)?;
+        Ok(Self::from_deck_data(deck_data, &mut guids))
+    }
+
+    /// Like `new_with_deck`, but the GUIDs of the deck and its cards are made by `guids` instead of being random. Use `GuidStrategy::generator` to build the same deck with the same GUIDs every time.
+    /// # Errors
+    /// Under any situation that the `GetCardInfo` implementations of the provided type would error.
+    pub fn new_with_deck_and_guids<T: GetCardInfo + Clone>(
+        deck: Vec<CardEntry<T>>,
+        mut guids: impl GuidGenerator,
+    ) -> Result<Self, CardError> {
+        let deck_data = generate_deck_data(deck, &DeckLimits::default(), None, &mut guids)?;
+        Ok(Self::from_deck_data(deck_data, &mut guids))
+    }
+
+    /// Like `new_with_deck`, but every card uses `back_url` as its back instead of the one from `GetCardInfo::get_back_image`, which isn't called, so it can't fail the deck. Since all backs are the same, no sheet is marked as having unique backs.
+    /// # Errors
+    /// - Under any situation that the `GetCardInfo` implementations of the provided type would error, except for `get_back_image`.
+    /// - If the deck has more cards than the default `DeckLimits` allow.
+    // Takes a `String` like the rest of the constructors and builders, even though it's only borrowed
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn new_with_deck_and_shared_back<T: GetCardInfo + Clone>(
+        deck: Vec<CardEntry<T>>,
+        back_url: String,
+    ) -> Result<Self, CardError> {
+        let mut guids = GuidStrategy::Random.generator();
+        let deck_data =
+            generate_deck_data(deck, &DeckLimits::default(), Some(&back_url), &mut guids)?;
+        Ok(Self::from_deck_data(deck_data, &mut guids))
+    }
+
+    /// Like `new_with_deck`, but cards whose `GetCardInfo::get_back_image` fails are handled according to `on_missing_back` instead of always failing the whole deck. Returns the save along with the errors of the cards that were given another back or skipped.
+    /// # Errors
+    /// - Under any situation that the `GetCardInfo` implementations of the provided type would error, except for `get_back_image` when `on_missing_back` isn't `MissingImagePolicy::Error`.
+    /// - If the deck has more cards than the default `DeckLimits` allow.
+    pub fn new_with_deck_and_back_policy<T: GetCardInfo + Clone>(
+        deck: Vec<CardEntry<T>>,
+        on_missing_back: &MissingImagePolicy,
+    ) -> Result<(Self, Vec<CardError>), CardError> {
+        let mut sheets = vec![];
+        let mut skipped = vec![];
+        for entry in resolve_deck(deck)? {
+            let back_url = match entry.card.get_back_image() {
+                Ok(back_url) => back_url,
+                Err(error) => match on_missing_back {
+                    MissingImagePolicy::Error => return Err(error),
+                    MissingImagePolicy::UseDefault(back_url) => {
+                        skipped.push(error);
+                        back_url.clone()
+                    }
+                    MissingImagePolicy::Skip => {
+                        skipped.push(error);
+                        continue;
+                    }
+                },
+            };
+            sheets.push((
+                entry.custom_deck_state_with_back(back_url)?,
+                vec![SheetCard::new(&entry)],
+            ));
+        }
+        let mut guids = GuidStrategy::Random.generator();
+        let deck_data = generate_sheets_data(sheets, &DeckLimits::default(), &mut guids)?;
+        Ok((Self::from_deck_data(deck_data, &mut guids), skipped))
+    }
+
+    /// Provides a `SaveState` with an infinite bag named `name` that gives out unlimited copies of `card`, which is how tokens are usually handed out in Tabletop Simulator.
+    /// # Errors
+    /// Under any situation that the `GetCardInfo` implementations of the provided type would error.
+    pub fn new_with_infinite_bag<T: GetCardInfo + Clone>(
+        name: String,
+        card: T,
+    ) -> Result<Self, CardError> {
+        let mut guids = GuidStrategy::Random.generator();
+        let (_, _, mut contained_objects) = generate_deck_data(
+            vec![CardEntry::new(card, NonZeroU32::MIN)],
+            &DeckLimits::default(),
+            None,
+            &mut guids,
+        )?;
+        let card_object = contained_objects.remove(0);
+        let bag = ObjectState::new_infinite_bag(card_object, &mut guids).with_nickname(name);
+        Ok(Self::from_object_states(vec![bag]))
+    }
+
+    /// Takes several named decks and provides a `SaveState` with all of them as separate objects, side by side so they don't overlap. Each deck's name is used as its label in Tabletop Simulator.
+    /// # Errors
+    /// Under any situation that the `GetCardInfo` implementations of the provided type would error.
+    pub fn new_with_decks<T: GetCardInfo + Clone>(
+        decks: Vec<(String, Vec<CardEntry<T>>)>,
+    ) -> Result<Self, CardError> {
+        let mut guids = GuidStrategy::Random.generator();
+        let mut object_states = vec![];
+        for (idx, (nickname, deck)) in (0_u32..).zip(decks) {
+            let deck_data = generate_deck_data(deck, &DeckLimits::default(), None, &mut guids)?;
+            let mut object_state = deck_object(deck_data, &mut guids);
+            object_state.nickname = nickname;
+            object_state.transform.pos_x = f64::from(idx) * DECK_SPACING;
+            object_states.push(object_state);
+        }
+        Ok(Self::from_object_states(object_states))
+    }
+
+    /// Like `new_with_deck`, but packs the cards into sprite sheets of up to `MAX_SHEET_WIDTH` by `MAX_SHEET_HEIGHT` cards instead of giving each card its own image. `face_urls` must have the URL of one composited image per sheet, laid out as described by `atlas_layout`, which is also returned. Each sheet uses the shape of its first card.
+    ///
+    /// If `back_urls` is provided, it must have the URL of one composited back image per sheet, laid out just like the fronts, and sheets containing cards with unique backs are marked as such. Otherwise, each sheet uses the back of its first card.
+    /// # Errors
+    /// - Under any situation that the `GetCardInfo` implementations of the provided type would error.
+    /// - If there isn't exactly one face URL per sheet, or exactly one back URL per sheet when they're provided.
+    /// - If a sheet has cards with unique backs but no back URLs are provided.
+    pub fn new_with_atlas<T: GetCardInfo + Clone>(
+        deck: &[CardEntry<T>],
+        face_urls: &[String],
+        back_urls: Option<&[String]>,
+    ) -> Result<(Self, Vec<SheetLayout>), CardError> {
+        let deck = resolve_deck(deck.to_vec())?;
+        let layout = atlas_layout(&deck);
+        if layout.len() != face_urls.len() {
+            return Err(CardError::custom(format!(
+                "The deck needs {} sheets, but {} face URLs were provided",
+                layout.len(),
+                face_urls.len()
+            )));
+        }
+        if let Some(back_urls) = back_urls.filter(|back_urls| back_urls.len() != layout.len()) {
+            return Err(CardError::custom(format!(
+                "The deck needs {} sheets, but {} back URLs were provided",
+                layout.len(),
+                back_urls.len()
+            )));
+        }
+        let mut guids = GuidStrategy::Random.generator();
+        let deck_data = generate_atlas_data(&deck, face_urls, back_urls, &mut guids)?;
+        Ok((Self::from_deck_data(deck_data, &mut guids), layout))
+    }
+
+    /// Reads a `SaveState` from the contents of a Tabletop Simulator save file.
+    /// # Errors
+    /// If the JSON isn't a valid save.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Serializes the save into the contents of a Tabletop Simulator save file.
+    /// # Errors
+    /// If serialization fails.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Like `to_json`, but pretty-printed.
+    /// # Errors
+    /// If serialization fails.
+    pub fn to_json_pretty(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Whether both saves are the same, comparing numbers with a tolerance of `APPROX_EQ_EPSILON` so that tests aren't broken by floats that lose precision when written and read back. Everything else is compared exactly.
+    #[must_use]
+    pub fn approx_eq(&self, other: &Self) -> bool {
+        match (serde_json::to_value(self), serde_json::to_value(other)) {
+            (Ok(value), Ok(other)) => values_approx_eq(&value, &other),
+            _ => false,
+        }
+    }
+
+    /// The face URL of each entry in the first deck of the save, along with how many cards in the deck use it, ordered by their key in the deck's `CustomDeck`. When the deck uses sprite sheets, the amount is the total for the whole sheet.
+    ///
+    /// Face URLs are returned instead of card names because Tabletop Simulator doesn't save the names of the cards of a `CustomDeck`, so a save read with `from_json` can only tell its cards apart by their images.
+    /// # Errors
+    /// If the save doesn't contain a deck.
+    pub fn deck_face_urls(&self) -> Result<Vec<(String, i64)>, CardError> {
+        let Some(deck) = self
+            .object_states
+            .iter()
+            .find(|object_state| object_state.name == "Deck")
+        else {
+            return Err(CardError::custom(
+                "The save doesn't contain a deck".to_string(),
+            ));
+        };
+        let mut amounts: BTreeMap<i64, i64> = BTreeMap::new();
+        for id in deck.deck_ids.iter().flatten() {
+            *amounts.entry(id / 100).or_default() += 1;
+        }
+        Ok(deck
+            .custom_deck
+            .iter()
+            .map(|(key, state)| {
+                (
+                    state.face_url.clone(),
+                    amounts.get(key).copied().unwrap_or_default(),
+                )
+            })
+            .collect())
+    }
+
+    /// The objects in the save.
+    #[must_use]
+    pub fn object_states(&self) -> &[ObjectState] {
+        &self.object_states
+    }
+
+    /// The objects in the save, for editing them, like removing cards from a loaded deck.
+    pub fn object_states_mut(&mut self) -> &mut [ObjectState] {
+        &mut self.object_states
+    }
+
+    /// The GUID of the first deck in the save, which Lua scripts can use to find the deck with `getObjectFromGUID`. `None` if the save doesn't contain a deck.
+    #[must_use]
+    pub fn deck_guid(&self) -> Option<&str> {
+        self.object_states
+            .iter()
+            .find(|object_state| object_state.name == "Deck")
+            .map(|deck| deck.guid.as_str())
+    }
+
+    /// Checks that no two objects in the save, including the cards inside decks, share a GUID. Tabletop Simulator can refuse to load saves with repeated GUIDs.
+    /// # Errors
+    /// If a GUID is used by more than one object.
+    pub fn assert_unique_guids(&self) -> Result<(), CardError> {
+        let mut guids = HashSet::new();
+        for object_state in &self.object_states {
+            object_state.check_unique_guids(&mut guids)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the name the object is shown with in Tabletop Simulator's object browser.
+    #[must_use]
+    pub fn with_save_name(mut self, save_name: String) -> Self {
+        self.save_name = save_name;
+        self
+    }
+
+    /// Sets the label of the save's first object, which is the deck in saves made by `new_with_deck` and similar constructors. Unlike the save name, this is what players see on the deck once it's spawned, instead of just "Deck".
+    #[must_use]
+    pub fn with_deck_name(mut self, deck_name: String) -> Self {
+        if let Some(object_state) = self.object_states.first_mut() {
+            object_state.nickname = deck_name;
+        }
+        self
+    }
+
+    /// Replaces the save's date, which is the current time when the save is created, with `date`, which is written verbatim. There's no clock on `wasm32-unknown-unknown`, so saves made there are undated unless a date is set.
+    #[must_use]
+    pub fn with_date(mut self, date: String) -> Self {
+        self.date = date;
+        self
+    }
+
+    /// Replaces the save's date, which is the current time when the save is created, with `time`, formatted by `tts_date`. Useful to pin the date, so that building the same deck twice gives the same JSON.
+    #[must_use]
+    pub fn with_date_at(self, time: SystemTime) -> Self {
+        self.with_date(tts_date(time))
+    }
+
+    /// Sets the version of Tabletop Simulator the save claims to be from. Defaults to `TTS_VERSION_NUMBER`.
+    #[must_use]
+    pub fn with_version_number(mut self, version_number: String) -> Self {
+        self.version_number = version_number;
+        self
+    }
+
+    /// Sets the save's game mode, which Tabletop Simulator shows as the name of the game. Defaults to `DEFAULT_GAME_MODE`.
+    #[must_use]
+    pub fn with_game_mode(mut self, game_mode: String) -> Self {
+        self.game_mode = game_mode;
+        self
+    }
+
+    /// Sets the save's tags.
+    #[must_use]
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Sets the save's gravity. Defaults to 0.5.
+    #[must_use]
+    pub const fn with_gravity(mut self, gravity: f64) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    /// Sets the save's play area. Defaults to 0.5.
+    #[must_use]
+    pub const fn with_play_area(mut self, play_area: f64) -> Self {
+        self.play_area = play_area;
+        self
+    }
+
+    /// Sets the save's table.
+    #[must_use]
+    pub fn with_table(mut self, table: String) -> Self {
+        self.table = table;
+        self
+    }
+
+    /// Sets the save's sky.
+    #[must_use]
+    pub fn with_sky(mut self, sky: String) -> Self {
+        self.sky = sky;
+        self
+    }
+
+    /// Sets the save's Lua script. It's stored verbatim, so it may contain any character.
+    #[must_use]
+    pub fn with_lua_script(mut self, lua_script: String) -> Self {
+        self.lua_script = lua_script;
+        self
+    }
+
+    /// Sets the save's Lua script state.
+    #[must_use]
+    pub fn with_lua_script_state(mut self, lua_script_state: String) -> Self {
+        self.lua_script_state = lua_script_state;
+        self
+    }
+
+    /// Sets whether cards hide their fronts when face down. Defaults to `true`, setting it to `false` is useful to show cards off rather than to play with them.
+    #[must_use]
+    pub fn with_hidden_backs(mut self, hidden_backs: bool) -> Self {
+        for object_state in &mut self.object_states {
+            object_state.set_hidden_backs(hidden_backs);
+        }
+        self
+    }
+
+    /// Sets where the save's objects spawn. When there are several decks, the first one is placed at `transform` and the rest keep their distance from it.
+    ///
+    /// Only the position of `transform` is used. Each object keeps its own rotation and scale, so decks stay face down and keep the scale of their cards. Use `with_orientation` to change the rotation.
+    #[must_use]
+    pub fn with_transform(mut self, transform: TransformState) -> Self {
+        let Some(origin) = self
+            .object_states
+            .first()
+            .map(|object_state| object_state.transform)
+        else {
+            return self;
         };
-        let object_states = vec![object_state];
-        Ok(Self {
+        for object_state in &mut self.object_states {
+            let current = object_state.transform;
+            object_state.transform = TransformState {
+                pos_x: transform.pos_x + current.pos_x - origin.pos_x,
+                pos_y: transform.pos_y + current.pos_y - origin.pos_y,
+                pos_z: transform.pos_z + current.pos_z - origin.pos_z,
+                ..current
+            };
+        }
+        self
+    }
+
+    /// Sets whether the save's objects spawn face down, which is the default, or face up, keeping their position.
+    #[must_use]
+    pub fn with_orientation(mut self, orientation: DeckOrientation) -> Self {
+        let rotation = orientation.rotation();
+        for object_state in &mut self.object_states {
+            object_state.transform = object_state.transform.with_rotation(rotation);
+        }
+        self
+    }
+
+    /// Sets the points objects snap to when dropped on the table.
+    #[must_use]
+    pub fn with_snap_points(mut self, snap_points: Vec<SnapPoint>) -> Self {
+        self.snap_points = snap_points;
+        self
+    }
+
+    /// Adds an object to the save, like a card or a deck built with `ObjectState`'s constructors.
+    #[must_use]
+    pub fn with_object(mut self, object_state: ObjectState) -> Self {
+        self.object_states.push(object_state);
+        self
+    }
+
+    fn from_deck_data(deck_data: DeckData, guids: &mut dyn GuidGenerator) -> Self {
+        Self::from_object_states(vec![deck_object(deck_data, guids)])
+    }
+
+    fn from_object_states(object_states: Vec<ObjectState>) -> Self {
+        Self {
             save_name: String::new(),
-            date: String::new(),
-            version_number: String::new(),
-            game_mode: String::new(),
+            date: current_tts_date(),
+            version_number: TTS_VERSION_NUMBER.to_owned(),
+            game_mode: DEFAULT_GAME_MODE.to_owned(),
             game_type: String::new(),
             game_complexity: String::new(),
             tags: vec![],
@@ -97,17 +480,143 @@ impl SaveState {
             table: String::new(),
             sky: String::new(),
             note: String::new(),
-            tab_states: HashMap::new(),
+            tab_states: BTreeMap::new(),
             lua_script: String::new(),
             lua_script_state: String::new(),
             xml_ui: String::new(),
+            snap_points: vec![],
             object_states,
-        })
+            extra: Map::new(),
+        }
+    }
+}
+
+/// The version of Tabletop Simulator that saves claim to be from by default.
+pub const TTS_VERSION_NUMBER: &str = "v13.2.2";
+/// The game mode saves have by default.
+pub const DEFAULT_GAME_MODE: &str = "Custom";
+
+/// Formats `time` the way Tabletop Simulator writes the `Date` of its saves, like `1/2/2025 3:04:05 PM`. The time is in UTC, and times before the Unix epoch are written as the epoch.
+#[must_use]
+pub fn tts_date(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (year, month, day) = civil_from_days(seconds / 86400);
+    let seconds_of_day = seconds % 86400;
+    let hour = seconds_of_day / 3600;
+    let (hour, period) = match hour {
+        0 => (12, "AM"),
+        1..=11 => (hour, "AM"),
+        12 => (12, "PM"),
+        _ => (hour - 12, "PM"),
+    };
+    format!(
+        "{month}/{day}/{year} {hour}:{:02}:{:02} {period}",
+        seconds_of_day % 3600 / 60,
+        seconds_of_day % 60
+    )
+}
+
+/// The current date in Tabletop Simulator's format. There's no clock on `wasm32-unknown-unknown`, so it's empty there.
+fn current_tts_date() -> String {
+    if cfg!(all(target_arch = "wasm32", target_os = "unknown")) {
+        String::new()
+    } else {
+        tts_date(SystemTime::now())
+    }
+}
+
+/// The year, month and day of the date `days` days after the Unix epoch, using the proleptic Gregorian calendar.
+const fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    // Howard Hinnant's `civil_from_days`, with years starting in March so that leap days are at their end
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// The largest difference between two numbers that `SaveState::approx_eq` considers equal.
+pub const APPROX_EQ_EPSILON: f64 = 1e-6;
+
+/// Whether two JSON values are equal, allowing floats to differ by up to `APPROX_EQ_EPSILON`. Integers are compared exactly.
+fn values_approx_eq(value: &Value, other: &Value) -> bool {
+    match (value, other) {
+        (Value::Number(number), Value::Number(other)) => match (number.as_i64(), other.as_i64()) {
+            (Some(number), Some(other)) => number == other,
+            _ => match (number.as_f64(), other.as_f64()) {
+                (Some(number), Some(other)) => (number - other).abs() <= APPROX_EQ_EPSILON,
+                _ => number == other,
+            },
+        },
+        (Value::Array(values), Value::Array(others)) => {
+            values.len() == others.len()
+                && values
+                    .iter()
+                    .zip(others)
+                    .all(|(value, other)| values_approx_eq(value, other))
+        }
+        (Value::Object(values), Value::Object(others)) => {
+            values.len() == others.len()
+                && values.iter().all(|(key, value)| {
+                    others
+                        .get(key)
+                        .is_some_and(|other| values_approx_eq(value, other))
+                })
+        }
+        (value, other) => value == other,
+    }
+}
+
+#[cfg(feature = "async")]
+impl SaveState {
+    /// Like `new_with_deck`, but for card types that implement `GetCardInfoAsync`. Each card's information is awaited in order.
+    /// # Errors
+    /// Under any situation that the `GetCardInfoAsync` implementations of the provided type would error.
+    pub async fn new_with_deck_async<T: GetCardInfoAsync + Send + Sync>(
+        deck: Vec<CardEntry<T>>,
+    ) -> Result<Self, CardError> {
+        let mut sheets = vec![];
+        for entry in deck {
+            let custom_deck_state = CustomDeckState {
+                name: entry.card.get_name().to_owned(),
+                face_url: entry.card.get_front_image().await?,
+                back_url: entry.card.get_back_image().await?,
+                num_width: Some(1),
+                num_height: Some(1),
+                back_is_hidden: true,
+                unique_back: entry.card.has_unique_back(),
+                r#type: entry.card.get_card_shape().await?,
+            };
+            let sheet_card = SheetCard {
+                amount: entry.amount.get(),
+                nickname: entry.card.get_name().to_owned(),
+                description: entry.card.get_description().unwrap_or_default(),
+                sideways: entry.card.is_sideways(),
+                scale: entry.card.get_scale(),
+            };
+            sheets.push((custom_deck_state, vec![sheet_card]));
+        }
+        let mut guids = GuidStrategy::Random.generator();
+        let deck_data = generate_sheets_data(sheets, &DeckLimits::default(), &mut guids)?;
+        Ok(Self::from_deck_data(deck_data, &mut guids))
     }
 }
 
 /// Tabletop Simulator card types. See [the TTS API docs](https://api.tabletopsimulator.com/custom-game-objects/#custom-card).
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum CardShape {
     RoundedRectangle,
     Rectangle,
@@ -128,6 +637,65 @@ impl From<CardShape> for i64 {
     }
 }
 
+impl CardShape {
+    /// The shape Tabletop Simulator identifies with `value`, if there's any.
+    #[must_use]
+    pub const fn from_i64(value: i64) -> Option<Self> {
+        match value {
+            0 => Some(Self::RoundedRectangle),
+            1 => Some(Self::Rectangle),
+            2 => Some(Self::RoundedHexagon),
+            3 => Some(Self::Hexagon),
+            4 => Some(Self::Circle),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<i64> for CardShape {
+    type Error = CardError;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        Self::from_i64(value)
+            .ok_or_else(|| CardError::custom(format!("{value} is not a valid card shape")))
+    }
+}
+
+impl Display for CardShape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RoundedRectangle => write!(f, "Rounded Rectangle"),
+            Self::Rectangle => write!(f, "Rectangle"),
+            Self::RoundedHexagon => write!(f, "Rounded Hexagon"),
+            Self::Hexagon => write!(f, "Hexagon"),
+            Self::Circle => write!(f, "Circle"),
+        }
+    }
+}
+
+impl FromStr for CardShape {
+    type Err = CardError;
+
+    /// Parses a shape from its name, ignoring case, spaces, dashes and underscores, so `"Rounded Rectangle"` and `"rounded_rectangle"` are the same. `rect` and `hex` are accepted as short for `rectangle` and `hexagon`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name: String = s
+            .chars()
+            .filter(|c| !matches!(c, ' ' | '-' | '_'))
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        match name.as_str() {
+            "roundedrectangle" | "roundedrect" => Ok(Self::RoundedRectangle),
+            "rectangle" | "rect" => Ok(Self::Rectangle),
+            "roundedhexagon" | "roundedhex" => Ok(Self::RoundedHexagon),
+            "hexagon" | "hex" => Ok(Self::Hexagon),
+            "circle" => Ok(Self::Circle),
+            _ => Err(CardError::custom(format!(
+                "{s} is not a valid card shape, expected one of rounded rectangle, rectangle, rounded hexagon, hexagon or circle"
+            ))),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 #[allow(clippy::struct_excessive_bools)]
@@ -141,7 +709,7 @@ pub struct ObjectState {
     #[serde(rename = "GMNotes")]
     gm_notes: String,
     alt_look_angle: Vector3,
-    color_difuse: ColourState,
+    color_diffuse: ColourState,
     layout_group_sort_index: i64,
     value: i64,
     locked: bool,
@@ -163,13 +731,230 @@ pub struct ObjectState {
     #[serde(rename = "DeckIDs")]
     #[serde(skip_serializing_if = "Option::is_none")]
     deck_ids: Option<Vec<i64>>,
-    custom_deck: HashMap<i64, CustomDeckState>,
+    custom_deck: BTreeMap<i64, CustomDeckState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    material_index: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bag: Option<BagState>,
     lua_script: String,
     lua_script_state: String,
     #[serde(rename = "XmlUI")]
     xml_ui: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    contained_objects: Option<Vec<ObjectState>>,
+    contained_objects: Option<Vec<Self>>,
+    /// Fields this implementation doesn't know about, kept so that they aren't lost when reading and writing back a save.
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+}
+
+impl ObjectState {
+    /// A face down deck made out of `contained_objects`, which should be cards. `deck_ids` has the `card_id` of each contained card, in the same order, and `custom_deck` has the `CustomDeckState` of every sheet the cards use. Its GUID is made by `guids`.
+    #[must_use]
+    pub fn new_deck(
+        deck_ids: Vec<i64>,
+        custom_deck: BTreeMap<i64, CustomDeckState>,
+        contained_objects: Vec<Self>,
+        guids: &mut dyn GuidGenerator,
+    ) -> Self {
+        Self {
+            transform: TransformState::face_down(),
+            deck_ids: Some(deck_ids),
+            custom_deck,
+            contained_objects: Some(contained_objects),
+            ..base_object_state("Deck", guids.next_guid("Deck"))
+        }
+    }
+
+    /// A card whose image is the one at `card_id` in `custom_deck`. See `card_id` for how card ids are built. Its GUID is made by `guids`.
+    #[must_use]
+    pub fn new_card(
+        card_id: i64,
+        custom_deck: BTreeMap<i64, CustomDeckState>,
+        guids: &mut dyn GuidGenerator,
+    ) -> Self {
+        Self {
+            hands: true,
+            card_id: Some(card_id),
+            custom_deck,
+            ..base_object_state(
+                "CardCustom",
+                guids.next_guid(&format!("CardCustom/{card_id}")),
+            )
+        }
+    }
+
+    /// A bag that gives out unlimited copies of `contained_object`, like a card built with `new_card`. Useful for tokens. Its GUID is made by `guids`.
+    #[must_use]
+    pub fn new_infinite_bag(contained_object: Self, guids: &mut dyn GuidGenerator) -> Self {
+        Self {
+            material_index: Some(-1),
+            bag: Some(BagState { order: 0 }),
+            contained_objects: Some(vec![contained_object]),
+            ..base_object_state("Infinite_Bag", guids.next_guid("Infinite_Bag"))
+        }
+    }
+
+    /// Sets the object's position, rotation and scale.
+    #[must_use]
+    pub const fn with_transform(mut self, transform: TransformState) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    /// Sets the name the object is shown with in Tabletop Simulator.
+    #[must_use]
+    pub fn with_nickname(mut self, nickname: String) -> Self {
+        self.nickname = nickname;
+        self
+    }
+
+    /// Sets the object's description.
+    #[must_use]
+    pub fn with_description(mut self, description: String) -> Self {
+        self.description = description;
+        self
+    }
+
+    /// Sets whether the card is in landscape orientation.
+    #[must_use]
+    pub const fn with_sideways_card(mut self, sideways_card: bool) -> Self {
+        self.sideways_card = sideways_card;
+        self
+    }
+
+    /// The object's GUID.
+    #[must_use]
+    pub fn guid(&self) -> &str {
+        &self.guid
+    }
+
+    /// The objects inside this one, like the cards of a deck. `None` for objects that can't contain others.
+    #[must_use]
+    pub fn contained_objects(&self) -> Option<&[Self]> {
+        self.contained_objects.as_deref()
+    }
+
+    /// The amount of cards in the object. For a deck it's the amount of objects in it, and for a card it's 1.
+    #[must_use]
+    pub fn card_count(&self) -> usize {
+        self.contained_objects
+            .as_ref()
+            .map_or_else(|| usize::from(self.card_id.is_some()), Vec::len)
+    }
+
+    /// Removes the contained object with the GUID `guid`, along with its id in `DeckIDs`. The sheet it used is removed from `CustomDeck` when no other card uses it. Returns whether the object was found.
+    pub fn remove_card_by_guid(&mut self, guid: &str) -> bool {
+        let Some(contained_objects) = &mut self.contained_objects else {
+            return false;
+        };
+        let Some(idx) = contained_objects
+            .iter()
+            .position(|contained_object| contained_object.guid == guid)
+        else {
+            return false;
+        };
+        let removed = contained_objects.remove(idx);
+        if let Some(deck_ids) = &mut self.deck_ids {
+            if idx < deck_ids.len() {
+                deck_ids.remove(idx);
+            }
+        }
+        if let Some(sheet_id) = removed.card_id.map(|card_id| card_id / 100) {
+            let sheet_used = contained_objects
+                .iter()
+                .filter_map(|contained_object| contained_object.card_id)
+                .any(|card_id| card_id / 100 == sheet_id);
+            if !sheet_used {
+                self.custom_deck.remove(&sheet_id);
+            }
+        }
+        true
+    }
+
+    /// Sets whether the object and everything in it hide their fronts when face down.
+    fn set_hidden_backs(&mut self, hidden_backs: bool) {
+        self.hide_when_face_down = hidden_backs;
+        for custom_deck_state in self.custom_deck.values_mut() {
+            custom_deck_state.back_is_hidden = hidden_backs;
+        }
+        for contained_object in self.contained_objects.iter_mut().flatten() {
+            contained_object.set_hidden_backs(hidden_backs);
+        }
+    }
+
+    /// Scales the object by `scale` in every direction.
+    const fn set_scale(&mut self, scale: f64) {
+        self.transform.scale_x = scale;
+        self.transform.scale_y = scale;
+        self.transform.scale_z = scale;
+    }
+
+    /// Adds the GUIDs of the object and everything in it to `guids`, erroring on the first one that's already there.
+    fn check_unique_guids<'a>(&'a self, guids: &mut HashSet<&'a str>) -> Result<(), CardError> {
+        if !guids.insert(&self.guid) {
+            return Err(CardError::custom(format!(
+                "The GUID {} is used by more than one object",
+                self.guid
+            )));
+        }
+        for contained_object in self.contained_objects.iter().flatten() {
+            contained_object.check_unique_guids(guids)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the object's Lua script.
+    #[must_use]
+    pub fn with_lua_script(mut self, lua_script: String) -> Self {
+        self.lua_script = lua_script;
+        self
+    }
+
+    /// Sets the object's Lua script state.
+    #[must_use]
+    pub fn with_lua_script_state(mut self, lua_script_state: String) -> Self {
+        self.lua_script_state = lua_script_state;
+        self
+    }
+}
+
+/// The fields shared by every kind of object, which constructors override as needed.
+fn base_object_state(name: &str, guid: String) -> ObjectState {
+    ObjectState {
+        guid,
+        name: name.to_string(),
+        transform: TransformState::default(),
+        nickname: String::new(),
+        description: String::new(),
+        gm_notes: String::new(),
+        alt_look_angle: Vector3::default(),
+        color_diffuse: ColourState::tts_default(),
+        layout_group_sort_index: 0,
+        value: 0,
+        locked: false,
+        grid: true,
+        snap: true,
+        ignore_fow: false,
+        measure_movement: false,
+        drag_selectable: true,
+        autoraise: true,
+        sticky: true,
+        tooltip: true,
+        grid_projection: false,
+        hide_when_face_down: true,
+        hands: false,
+        card_id: None,
+        sideways_card: false,
+        deck_ids: None,
+        custom_deck: BTreeMap::new(),
+        material_index: None,
+        bag: None,
+        lua_script: String::new(),
+        lua_script_state: String::new(),
+        xml_ui: String::new(),
+        contained_objects: None,
+        extra: Map::new(),
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Serialize, Deserialize, Clone)]
@@ -184,71 +969,627 @@ pub struct CustomDeckState {
     pub(super) num_height: Option<i64>,
     pub(super) back_is_hidden: bool,
     pub(super) unique_back: bool,
-    pub(super) r#type: i64,
+    #[serde(with = "card_shape_as_i64")]
+    pub(super) r#type: CardShape,
+}
+
+/// Serializes a `CardShape` as the integer Tabletop Simulator uses for it.
+mod card_shape_as_i64 {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    use super::CardShape;
+
+    // serde's `with` requires taking the value by reference
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn serialize<S: Serializer>(shape: &CardShape, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64((*shape).into())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<CardShape, D::Error> {
+        let value = i64::deserialize(deserializer)?;
+        CardShape::try_from(value).map_err(D::Error::custom)
+    }
+}
+
+type DeckData = (Vec<i64>, BTreeMap<i64, CustomDeckState>, Vec<ObjectState>);
+
+/// The distance between the decks of a save with multiple decks.
+const DECK_SPACING: f64 = 2.5;
+
+fn deck_object(
+    (deck_ids, custom_deck, contained_objects): DeckData,
+    guids: &mut dyn GuidGenerator,
+) -> ObjectState {
+    let scale = contained_objects
+        .first()
+        .map_or(1.0, |card| card.transform.scale_x);
+    let mut object_state = ObjectState::new_deck(deck_ids, custom_deck, contained_objects, guids);
+    object_state.set_scale(scale);
+    object_state
+}
+
+/// Tabletop Simulator's maximum amount of columns in a sprite sheet.
+pub const MAX_SHEET_WIDTH: usize = 10;
+/// Tabletop Simulator's maximum amount of rows in a sprite sheet.
+pub const MAX_SHEET_HEIGHT: usize = 7;
+
+/// How the cards of a sprite sheet are laid out, so that its image can be composited.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SheetLayout {
+    /// The amount of columns in the sheet.
+    pub num_width: usize,
+    /// The amount of rows in the sheet.
+    pub num_height: usize,
+    /// The names of the cards in the sheet, in row-major order. The last row may be incomplete.
+    pub cards: Vec<String>,
+    /// Whether any card in the sheet has a unique back, in which case the backs need a sheet with the same layout as the fronts.
+    pub unique_back: bool,
+}
+
+/// Groups the cards of a deck into sprite sheets of up to `MAX_SHEET_WIDTH` by `MAX_SHEET_HEIGHT` cards, in the same order `SaveState::new_with_atlas` uses.
+#[must_use]
+pub fn atlas_layout<T: GetCardInfo + Clone>(deck: &[CardEntry<T>]) -> Vec<SheetLayout> {
+    deck.chunks(MAX_SHEET_WIDTH * MAX_SHEET_HEIGHT)
+        .map(|cards| {
+            let (num_width, num_height) = sheet_dimensions(cards.len());
+            SheetLayout {
+                num_width,
+                num_height,
+                cards: cards
+                    .iter()
+                    .map(|entry| entry.card.get_name().to_owned())
+                    .collect(),
+                unique_back: cards.iter().any(|entry| entry.card.has_unique_back()),
+            }
+        })
+        .collect()
 }
 
-type DeckData = (Vec<i64>, HashMap<i64, CustomDeckState>, Vec<ObjectState>);
+/// The columns and rows of the smallest sheet that fits `cards` cards.
+const fn sheet_dimensions(cards: usize) -> (usize, usize) {
+    let num_width = if cards < MAX_SHEET_WIDTH {
+        cards
+    } else {
+        MAX_SHEET_WIDTH
+    };
+    (num_width, cards.div_ceil(num_width))
+}
 
+/// The data of a deck with a sheet for each entry. When `shared_back` is given, every card uses it as its back and `GetCardInfo::get_back_image` isn't called.
 fn generate_deck_data<T: GetCardInfo + Clone>(
     deck: Vec<CardEntry<T>>,
+    limits: &DeckLimits,
+    shared_back: Option<&str>,
+    guids: &mut dyn GuidGenerator,
 ) -> Result<DeckData, CardError> {
-    let mut card_ids = vec![];
-    let mut custom_deck = HashMap::new();
-    let mut contained_objects = vec![];
-    let mut idx: i64 = 0;
-    for card in deck {
-        idx += 1;
-        let id = idx * 100;
-        custom_deck.insert(idx, card.get_custom_deck_state()?);
-        for _ in 0..card.amount {
-            card_ids.push(id);
-            contained_objects.push(ObjectState {
-                guid: generate_guid(),
-                name: "CardCustom".to_string(),
-                transform: TransformState::default(),
-                nickname: String::new(),
-                description: String::new(),
-                gm_notes: String::new(),
-                alt_look_angle: Vector3::default(),
-                color_difuse: ColourState {
-                    r: 0.713_235_259,
-                    g: 0.713_235_259,
-                    b: 0.713_235_259,
-                },
-                layout_group_sort_index: 0,
-                value: 0,
-                locked: false,
-                grid: true,
-                snap: true,
-                ignore_fow: false,
-                measure_movement: false,
-                drag_selectable: true,
-                autoraise: true,
-                sticky: true,
-                tooltip: true,
-                grid_projection: false,
-                hide_when_face_down: true,
-                hands: true,
-                card_id: Some(id),
-                sideways_card: false,
-                deck_ids: None,
-                custom_deck: {
-                    let mut hm = HashMap::new();
-                    hm.insert(idx, card.get_custom_deck_state()?);
-                    hm
-                },
-                lua_script: String::new(),
-                lua_script_state: String::new(),
-                xml_ui: String::new(),
-                contained_objects: None,
-            });
-        }
+    let mut sheets = vec![];
+    for card in resolve_deck(deck)? {
+        let custom_deck_state = match shared_back {
+            Some(back_url) => CustomDeckState {
+                unique_back: false,
+                ..card.custom_deck_state_with_back(back_url.to_owned())?
+            },
+            None => card.get_custom_deck_state()?,
+        };
+        sheets.push((custom_deck_state, vec![SheetCard::new(&card)]));
     }
-    Ok((card_ids, custom_deck, contained_objects))
+    generate_sheets_data(sheets, limits, guids)
 }
 
-/// Implementation of Tabletop Simulator's `TransformState`. While it would be strange for this structure to contain more fields than the ones in this implementation, fields may be missing because the [knowledge base](https://kb.tabletopsimulator.com/custom-content/save-file-format/) is currently outdated.
-#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Copy)]
+// Sheets are at most 10 by 7 cards, so their dimensions always fit in an i64
+#[allow(clippy::cast_possible_wrap)]
+fn generate_atlas_data<T: GetCardInfo + Clone>(
+    deck: &[CardEntry<T>],
+    face_urls: &[String],
+    back_urls: Option<&[String]>,
+    guids: &mut dyn GuidGenerator,
+) -> Result<DeckData, CardError> {
+    let mut sheets = vec![];
+    for (sheet_idx, (cards, face_url)) in deck
+        .chunks(MAX_SHEET_WIDTH * MAX_SHEET_HEIGHT)
+        .zip(face_urls)
+        .enumerate()
+    {
+        let (num_width, num_height) = sheet_dimensions(cards.len());
+        let first = &cards[0].card;
+        let unique_back = cards.iter().any(|entry| entry.card.has_unique_back());
+        let back_url = match back_urls {
+            Some(back_urls) => back_urls[sheet_idx].clone(),
+            None if unique_back => {
+                return Err(CardError::custom(format!(
+                    "Sheet {} has cards with unique backs, but no back URLs were provided",
+                    sheet_idx + 1
+                )))
+            }
+            None => first.get_back_image()?,
+        };
+        let custom_deck_state = CustomDeckState {
+            name: String::new(),
+            face_url: face_url.clone(),
+            back_url,
+            num_width: Some(num_width as i64),
+            num_height: Some(num_height as i64),
+            back_is_hidden: true,
+            unique_back,
+            r#type: first.get_card_shape()?,
+        };
+        sheets.push((
+            custom_deck_state,
+            cards.iter().map(SheetCard::new).collect(),
+        ));
+    }
+    generate_sheets_data(sheets, &DeckLimits::default(), guids)
+}
+
+/// The `card_id` Tabletop Simulator uses for the card in row `row` and column `column` of the sheet with the key `sheet_id` in a `CustomDeck`.
+///
+/// It's `None` if the card is outside of a sheet of `MAX_SHEET_WIDTH` by `MAX_SHEET_HEIGHT` cards, or if the id doesn't fit in an `i64`.
+///
+/// Tabletop Simulator numbers the cards of a sheet in row-major order, starting at 0 in the top-left corner. Every sheet with more than one row is `MAX_SHEET_WIDTH` cards wide, so each row starts 10 ids after the previous one, and a 2 by 2 grid of cards in the first sheet has the ids 100, 101, 110 and 111.
+// Sheets are at most 10 by 7 cards, so their dimensions always fit in an i64
+#[allow(clippy::cast_possible_wrap)]
+#[must_use]
+pub const fn card_id(sheet_id: i64, row: u8, column: u8) -> Option<i64> {
+    if row as usize >= MAX_SHEET_HEIGHT || column as usize >= MAX_SHEET_WIDTH {
+        return None;
+    }
+    let position = row as i64 * MAX_SHEET_WIDTH as i64 + column as i64;
+    match sheet_id.checked_mul(100) {
+        Some(id) => id.checked_add(position),
+        None => None,
+    }
+}
+
+/// Like `card_id`, but for the card at `position` in row-major order, and with an error for when the id doesn't fit in an `i64`.
+fn checked_card_id(sheet_id: i64, position: usize) -> Result<i64, CardError> {
+    let row = u8::try_from(position / MAX_SHEET_WIDTH).ok();
+    let column = u8::try_from(position % MAX_SHEET_WIDTH).ok();
+    let id = match (row, column) {
+        (Some(row), Some(column)) => card_id(sheet_id, row, column),
+        _ => None,
+    };
+    id.ok_or_else(|| {
+        CardError::custom(format!(
+            "The card ids of sheet {sheet_id} don't fit in an i64, so the deck has too many sheets"
+        ))
+    })
+}
+
+/// What to do with a card whose image can't be obtained.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum MissingImagePolicy {
+    /// Fail the whole build.
+    #[default]
+    Error,
+    /// Use this image instead.
+    UseDefault(String),
+    /// Leave the card out.
+    Skip,
+}
+
+/// How the GUIDs of the objects in a save are generated. Use `generator` to get a `GuidGenerator` for it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GuidStrategy {
+    /// Random GUIDs, so every save of the same deck has different ones.
+    #[default]
+    Random,
+    /// GUIDs derived from `namespace` and each object's name and place in the save, so building the same deck twice gives the same GUIDs.
+    Deterministic { namespace: Uuid },
+}
+
+impl GuidStrategy {
+    /// A generator that makes GUIDs with this strategy, starting from the first object of a save.
+    #[must_use]
+    pub const fn generator(self) -> StrategyGuids {
+        StrategyGuids {
+            strategy: self,
+            count: 0,
+        }
+    }
+}
+
+/// A source of GUIDs for the objects in a save, which is given to constructors like `SaveState::new_with_deck_and_guids` and `ObjectState::new_card`.
+///
+/// Objects are given their GUID as they're built, the cards of a deck before the deck itself. It's implemented by `StrategyGuids`, and by any `FnMut() -> String`, like a closure that counts up to give predictable GUIDs in tests.
+pub trait GuidGenerator {
+    /// A GUID for an object of the kind described by `key`, like `Deck` or `CardCustom/100`. Different objects can have the same key, like the copies of a card.
+    fn next_guid(&mut self, key: &str) -> String;
+}
+
+/// The `GuidGenerator` of a `GuidStrategy`. Deterministic GUIDs are derived from the key and from how many GUIDs were made before, so that objects with the same key still get different GUIDs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StrategyGuids {
+    strategy: GuidStrategy,
+    count: u64,
+}
+
+impl GuidGenerator for StrategyGuids {
+    fn next_guid(&mut self, key: &str) -> String {
+        let guid = match self.strategy {
+            GuidStrategy::Random => Uuid::new_v4().to_string(),
+            GuidStrategy::Deterministic { namespace } => {
+                Uuid::new_v5(&namespace, format!("{}/{key}", self.count).as_bytes()).to_string()
+            }
+        };
+        self.count += 1;
+        guid
+    }
+}
+
+impl<F: FnMut() -> String> GuidGenerator for F {
+    fn next_guid(&mut self, _key: &str) -> String {
+        self()
+    }
+}
+
+/// Limits on the amount of cards in a deck, so that absurd amounts produce an error instead of trying to build an equally absurd amount of objects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DeckLimits {
+    /// The most copies a single entry of the deck can have.
+    pub max_copies_per_card: u32,
+    /// The most cards the whole deck can have.
+    pub max_total_cards: u64,
+}
+
+impl Default for DeckLimits {
+    fn default() -> Self {
+        Self {
+            max_copies_per_card: 1000,
+            max_total_cards: 100_000,
+        }
+    }
+}
+
+impl DeckLimits {
+    /// Checks that `amount` copies of `card_name` fit within the limits, adding them to `total`, which is the amount of cards so far.
+    fn check(&self, card_name: &str, amount: u32, total: &mut u64) -> Result<(), CardError> {
+        if amount > self.max_copies_per_card {
+            return Err(CardError::custom(format!(
+                "{card_name} has {amount} copies, but decks can have at most {} copies of each card",
+                self.max_copies_per_card
+            )));
+        }
+        *total = total.saturating_add(amount.into());
+        if *total > self.max_total_cards {
+            return Err(CardError::custom(format!(
+                "The deck has more than {} cards",
+                self.max_total_cards
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// The information needed to build the objects for every copy of a card in a sheet.
+struct SheetCard {
+    amount: u32,
+    nickname: String,
+    description: String,
+    sideways: bool,
+    scale: f64,
+}
+
+impl SheetCard {
+    fn new<T: GetCardInfo + Clone>(entry: &CardEntry<T>) -> Self {
+        Self {
+            amount: entry.amount.get(),
+            nickname: entry.card.get_name().to_owned(),
+            description: entry.card.get_description().unwrap_or_default(),
+            sideways: entry.card.is_sideways(),
+            scale: entry.card.get_scale(),
+        }
+    }
+}
+
+/// Builds the deck data out of each sheet's `CustomDeckState` and the cards in the sheet, in order.
+/// # Errors
+/// If the deck has more cards than `limits` allows, or too many sheets for their card ids to fit in an `i64`.
+fn generate_sheets_data(
+    sheets: Vec<(CustomDeckState, Vec<SheetCard>)>,
+    limits: &DeckLimits,
+    guids: &mut dyn GuidGenerator,
+) -> Result<DeckData, CardError> {
+    let mut card_ids = vec![];
+    let mut custom_deck = BTreeMap::new();
+    let mut contained_objects = vec![];
+    let mut total = 0;
+    for (idx, (custom_deck_state, cards)) in (1..).zip(sheets) {
+        for (position, card) in (0..).zip(cards) {
+            limits.check(&card.nickname, card.amount, &mut total)?;
+            let id = checked_card_id(idx, position)?;
+            for _ in 0..card.amount {
+                card_ids.push(id);
+                contained_objects.push(card_object(idx, id, &custom_deck_state, &card, guids));
+            }
+        }
+        custom_deck.insert(idx, custom_deck_state);
+    }
+    Ok((card_ids, custom_deck, contained_objects))
+}
+
+/// The object for one copy of `card`, whose id is `card_id` in the sheet with the key `sheet_id`.
+fn card_object(
+    sheet_id: i64,
+    card_id: i64,
+    custom_deck_state: &CustomDeckState,
+    card: &SheetCard,
+    guids: &mut dyn GuidGenerator,
+) -> ObjectState {
+    let mut card_custom_deck = BTreeMap::new();
+    card_custom_deck.insert(sheet_id, custom_deck_state.clone());
+    let mut object_state = ObjectState::new_card(card_id, card_custom_deck, guids)
+        .with_nickname(card.nickname.clone())
+        .with_description(card.description.clone())
+        .with_sideways_card(card.sideways);
+    object_state.set_scale(card.scale);
+    object_state
+}
+
+/// Iterates over the object of every copy of every card in a deck, building them one at a time. The objects are the same ones `SaveState::new_with_deck` puts in its deck.
+pub struct CardObjects<'a, T> {
+    entries: std::iter::Zip<std::ops::RangeFrom<i64>, std::vec::IntoIter<CardEntry<T>>>,
+    /// The `CustomDeckState` of every entry, if they were already generated.
+    custom_deck: Option<&'a BTreeMap<i64, CustomDeckState>>,
+    /// The key of the current entry's sheet, the card's id, its `CustomDeckState`, the information of the card, and how many copies are left.
+    current: Option<(i64, i64, CustomDeckState, SheetCard, u32)>,
+    /// Where the GUIDs of the cards come from.
+    guids: StrategyGuids,
+    /// The error of `GetCardInfo::resolve_batch`, which is the only item if it failed.
+    resolve_error: Option<CardError>,
+}
+
+impl<T: GetCardInfo + Clone> Iterator for CardObjects<'_, T> {
+    type Item = Result<ObjectState, CardError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.resolve_error.take() {
+            return Some(Err(error));
+        }
+        loop {
+            if let Some((sheet_id, card_id, custom_deck_state, card, remaining)) = &mut self.current
+            {
+                if *remaining > 0 {
+                    *remaining -= 1;
+                    return Some(Ok(card_object(
+                        *sheet_id,
+                        *card_id,
+                        custom_deck_state,
+                        card,
+                        &mut self.guids,
+                    )));
+                }
+            }
+            self.current = None;
+            let (sheet_id, entry) = self.entries.next()?;
+            let card_id = match checked_card_id(sheet_id, 0) {
+                Ok(card_id) => card_id,
+                Err(error) => return Some(Err(error)),
+            };
+            let custom_deck_state = match self.custom_deck {
+                Some(custom_deck) => custom_deck[&sheet_id].clone(),
+                None => match entry.get_custom_deck_state() {
+                    Ok(custom_deck_state) => custom_deck_state,
+                    Err(error) => return Some(Err(error)),
+                },
+            };
+            self.current = Some((
+                sheet_id,
+                card_id,
+                custom_deck_state,
+                SheetCard::new(&entry),
+                entry.amount.get(),
+            ));
+        }
+    }
+}
+
+/// Iterates over the object of every copy of every card in `deck` without building all of them at once.
+///
+/// Cards whose information can't be obtained produce an error and are skipped. The cards have random GUIDs. The deck is given to `GetCardInfo::resolve_batch` first. If that fails, its error is the only item.
+#[must_use]
+pub fn card_objects<T: GetCardInfo + Clone>(deck: &[CardEntry<T>]) -> CardObjects<'_, T> {
+    let (deck, resolve_error) = match resolve_deck(deck.to_vec()) {
+        Ok(deck) => (deck, None),
+        Err(error) => (vec![], Some(error)),
+    };
+    CardObjects {
+        entries: (1..).zip(deck),
+        custom_deck: None,
+        current: None,
+        guids: GuidStrategy::Random.generator(),
+        resolve_error,
+    }
+}
+
+/// Writes the JSON of a save with a single deck, like the one from `SaveState::new_with_deck`, without building the objects of all the cards at once. Only one card object is held in memory at a time, which keeps memory usage low for decks with many copies of their cards.
+pub struct DeckWriter<'a, T> {
+    deck: &'a [CardEntry<T>],
+    save: SaveState,
+    pretty: bool,
+    limits: DeckLimits,
+}
+
+impl<'a, T: GetCardInfo + Clone> DeckWriter<'a, T> {
+    #[must_use]
+    pub fn new(deck: &'a [CardEntry<T>]) -> Self {
+        Self {
+            deck,
+            save: SaveState::new(),
+            pretty: false,
+            limits: DeckLimits::default(),
+        }
+    }
+
+    /// Uses the settings of `save`, like its name or its tags. Any objects in `save` are written after the deck.
+    #[must_use]
+    pub fn with_save(mut self, save: SaveState) -> Self {
+        self.save = save;
+        self
+    }
+
+    /// Whether the JSON should be pretty-printed.
+    #[must_use]
+    pub const fn with_pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+
+    /// Sets the limits on the amount of cards in the deck.
+    #[must_use]
+    pub const fn with_limits(mut self, limits: DeckLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Writes the save into `writer`.
+    /// # Errors
+    /// - `Error::Card` under any situation that the `GetCardInfo` implementations of the provided type would error, or if the deck has more cards than its limits allow. Nothing is written in these cases.
+    /// - `Error::Json` if writing fails.
+    pub fn write<W: io::Write>(&self, writer: W) -> Result<(), crate::Error> {
+        let deck = resolve_deck(self.deck.to_vec())?;
+        let mut custom_deck = BTreeMap::new();
+        let mut deck_ids = vec![];
+        let mut total = 0;
+        for (sheet_id, entry) in (1..).zip(&deck) {
+            self.limits
+                .check(entry.card.get_name(), entry.amount.get(), &mut total)?;
+            let card_id = checked_card_id(sheet_id, 0)?;
+            custom_deck.insert(sheet_id, entry.get_custom_deck_state()?);
+            for _ in 0..entry.amount.get() {
+                deck_ids.push(card_id);
+            }
+        }
+        let mut guids = GuidStrategy::Random.generator();
+        let mut deck_object = ObjectState {
+            contained_objects: None,
+            ..ObjectState::new_deck(deck_ids, custom_deck.clone(), vec![], &mut guids)
+        };
+        if let Some(first) = deck.first() {
+            deck_object.set_scale(first.card.get_scale());
+        }
+        let streamed = StreamedSave {
+            save: &self.save,
+            deck: StreamedDeck {
+                object_state: &deck_object,
+                contained_objects: StreamedCards {
+                    deck: &deck,
+                    custom_deck: &custom_deck,
+                    guids,
+                },
+            },
+        };
+        if self.pretty {
+            streamed.serialize(&mut serde_json::Serializer::pretty(writer))?;
+        } else {
+            streamed.serialize(&mut serde_json::Serializer::new(writer))?;
+        }
+        Ok(())
+    }
+}
+
+/// A `SaveState` whose first object is a deck whose cards are serialized as they're built.
+struct StreamedSave<'a, T> {
+    save: &'a SaveState,
+    deck: StreamedDeck<'a, T>,
+}
+
+impl<T: GetCardInfo + Clone> Serialize for StreamedSave<'_, T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let save = self.save;
+        let mut state = serializer.serialize_map(None)?;
+        state.serialize_entry("SaveName", &save.save_name)?;
+        state.serialize_entry("Date", &save.date)?;
+        state.serialize_entry("VersionNumber", &save.version_number)?;
+        state.serialize_entry("GameMode", &save.game_mode)?;
+        state.serialize_entry("GameType", &save.game_type)?;
+        state.serialize_entry("GameComplexity", &save.game_complexity)?;
+        state.serialize_entry("Tags", &save.tags)?;
+        state.serialize_entry("Gravity", &save.gravity)?;
+        state.serialize_entry("PlayArea", &save.play_area)?;
+        state.serialize_entry("Table", &save.table)?;
+        state.serialize_entry("Sky", &save.sky)?;
+        state.serialize_entry("Note", &save.note)?;
+        state.serialize_entry("TabStates", &save.tab_states)?;
+        state.serialize_entry("LuaScript", &save.lua_script)?;
+        state.serialize_entry("LuaScriptState", &save.lua_script_state)?;
+        state.serialize_entry("XmlUI", &save.xml_ui)?;
+        if !save.snap_points.is_empty() {
+            state.serialize_entry("SnapPoints", &save.snap_points)?;
+        }
+        state.serialize_entry(
+            "ObjectStates",
+            &StreamedObjects {
+                deck: &self.deck,
+                others: &save.object_states,
+            },
+        )?;
+        for (key, value) in &save.extra {
+            state.serialize_entry(key, value)?;
+        }
+        state.end()
+    }
+}
+
+/// The objects of a `StreamedSave`, which are its deck followed by any other objects.
+struct StreamedObjects<'a, T> {
+    deck: &'a StreamedDeck<'a, T>,
+    others: &'a [ObjectState],
+}
+
+impl<T: GetCardInfo + Clone> Serialize for StreamedObjects<'_, T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeSeq;
+
+        let mut seq = serializer.serialize_seq(Some(self.others.len() + 1))?;
+        seq.serialize_element(self.deck)?;
+        for object_state in self.others {
+            seq.serialize_element(object_state)?;
+        }
+        seq.end()
+    }
+}
+
+/// A deck object whose `contained_objects` are serialized as they're built.
+#[derive(Serialize)]
+#[serde(bound(serialize = "T: GetCardInfo + Clone"))]
+struct StreamedDeck<'a, T> {
+    #[serde(flatten)]
+    object_state: &'a ObjectState,
+    #[serde(rename = "ContainedObjects")]
+    contained_objects: StreamedCards<'a, T>,
+}
+
+/// The cards of a deck, which are built one at a time as they're serialized.
+struct StreamedCards<'a, T> {
+    deck: &'a [CardEntry<T>],
+    custom_deck: &'a BTreeMap<i64, CustomDeckState>,
+    /// Where the GUIDs of the cards come from, which is after the deck's GUID.
+    guids: StrategyGuids,
+}
+
+impl<T: GetCardInfo + Clone> Serialize for StreamedCards<'_, T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{Error, SerializeSeq};
+
+        let cards = CardObjects {
+            entries: (1..).zip(self.deck.to_vec()),
+            custom_deck: Some(self.custom_deck),
+            current: None,
+            guids: self.guids,
+            resolve_error: None,
+        };
+        let mut seq = serializer.serialize_seq(None)?;
+        for object_state in cards {
+            seq.serialize_element(&object_state.map_err(S::Error::custom)?)?;
+        }
+        seq.end()
+    }
+}
+
+/// Implementation of Tabletop Simulator's `TransformState`. While it would be strange for this structure to contain more fields than the ones in this implementation, fields may be missing because the [knowledge base](https://kb.tabletopsimulator.com/custom-content/save-file-format/) is currently outdated.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
 pub struct TransformState {
     pub pos_x: f64,
@@ -264,10 +1605,18 @@ pub struct TransformState {
 
 impl Default for TransformState {
     fn default() -> Self {
+        Self::at(Vector3::new(0.0, 0.0, 0.0))
+    }
+}
+
+impl TransformState {
+    /// A transform at `pos`, with no rotation and a scale of 1.
+    #[must_use]
+    pub const fn at(pos: Vector3) -> Self {
         Self {
-            pos_x: 0.0,
-            pos_y: 0.0,
-            pos_z: 0.0,
+            pos_x: pos.x,
+            pos_y: pos.y,
+            pos_z: pos.z,
             rot_x: 0.0,
             rot_y: 0.0,
             rot_z: 0.0,
@@ -276,6 +1625,42 @@ impl Default for TransformState {
             scale_z: 1.0,
         }
     }
+
+    /// A transform at the origin, rotated like `DeckOrientation::FaceDown` so that cards and decks lie face down.
+    #[must_use]
+    pub const fn face_down() -> Self {
+        Self::at(Vector3::new(0.0, 0.0, 0.0)).with_rotation(DeckOrientation::FaceDown.rotation())
+    }
+
+    /// Sets the rotation, in degrees around each axis.
+    #[must_use]
+    pub const fn with_rotation(mut self, rotation: Vector3) -> Self {
+        self.rot_x = rotation.x;
+        self.rot_y = rotation.y;
+        self.rot_z = rotation.z;
+        self
+    }
+}
+
+/// Which side of the cards faces up when a deck spawns.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DeckOrientation {
+    /// The backs face up, like a deck ready to be drawn from.
+    #[default]
+    FaceDown,
+    /// The fronts face up, like a deck on display.
+    FaceUp,
+}
+
+impl DeckOrientation {
+    /// The rotation, in degrees, that gives this orientation. Tabletop Simulator flips objects around the Z axis, while the 180 degrees around the Y axis turn the cards to face the player at the default seat.
+    #[must_use]
+    pub const fn rotation(self) -> Vector3 {
+        match self {
+            Self::FaceDown => Vector3::new(0.0, 180.0, 180.0),
+            Self::FaceUp => Vector3::new(0.0, 180.0, 0.0),
+        }
+    }
 }
 
 /// Implementation of Tabletop Simulator's Vector3. While it would be strange for this structure to contain more fields than the ones in this implementation, fields may be missing because the [knowledge base](https://kb.tabletopsimulator.com/custom-content/save-file-format/) is currently outdated.
@@ -286,6 +1671,13 @@ pub struct Vector3 {
     pub z: f64,
 }
 
+impl Vector3 {
+    #[must_use]
+    pub const fn new(x: f64, y: f64, z: f64) -> Self {
+        Self { x, y, z }
+    }
+}
+
 /// Implementation of Tabletop Simulator's `ColourState`. While it would be strange for this structure to contain more fields than the ones in this implementation, fields may be missing because the [knowledge base](https://kb.tabletopsimulator.com/custom-content/save-file-format/) is currently outdated.
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Copy)]
 pub struct ColourState {
@@ -294,10 +1686,43 @@ pub struct ColourState {
     pub b: f64,
 }
 
+impl ColourState {
+    /// The grey tint Tabletop Simulator gives to objects by default.
+    #[must_use]
+    pub const fn tts_default() -> Self {
+        Self {
+            r: 0.713_235_259,
+            g: 0.713_235_259,
+            b: 0.713_235_259,
+        }
+    }
+}
+
+/// Implementation of Tabletop Simulator's `SnapPoint`, a point on the table that objects snap to. Fields may be missing because the [knowledge base](https://kb.tabletopsimulator.com/custom-content/save-file-format/) is currently outdated.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct SnapPoint {
+    pub position: Vector3,
+    pub rotation: Vector3,
+    /// Only objects with at least one of these tags snap to the point. Every object does if there are none.
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Implementation of Tabletop Simulator's `BagState`, which bags have. Fields may be missing because the [knowledge base](https://kb.tabletopsimulator.com/custom-content/save-file-format/) is currently outdated.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "PascalCase")]
+pub struct BagState {
+    /// The order objects are taken out of the bag in. 0 is last in first out, 1 is first in first out, and 2 is random.
+    pub order: i64,
+}
+
+#[derive(Debug)]
 pub enum SaveError {
     CouldntWriteObject { path: PathBuf, error: io::Error },
     CouldntWriteImage { path: PathBuf, error: io::Error },
     CouldntFindSaveDirectory,
+    CouldntCreateSaveDirectory { path: PathBuf, error: io::Error },
 }
 
 impl Display for SaveError {
@@ -316,69 +1741,1381 @@ impl Display for SaveError {
             Self::CouldntFindSaveDirectory => {
                 write!(f, "Couldn't find Tabletop Simulator's saved object files")
             }
+            Self::CouldntCreateSaveDirectory { path, error } => write!(
+                f,
+                "Failed to create the directory {} with error: {error}",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::CouldntWriteObject { error, .. }
+            | Self::CouldntWriteImage { error, .. }
+            | Self::CouldntCreateSaveDirectory { error, .. } => Some(error),
+            Self::CouldntFindSaveDirectory => None,
         }
     }
 }
 
-/// Writes the object to the default TTS save directory. The image is mandatory.
+/// Writes the object to the default TTS save directory, creating it if it doesn't exist yet. The image is mandatory, use `write_json_to_tts_dir` to write only the object.
 /// # Errors
 /// - If the object json file can't be written
 /// - If the object image file can't be written
-/// - If the default TTS save directory can't be found
+/// - If the default TTS save directory can't be found or created
+#[cfg(feature = "fs")]
 pub fn write_to_tts_dir<P: AsRef<Path>, Cc: AsRef<[u8]>, Ci: AsRef<[u8]>>(
     output: P,
     contents: Cc,
     image: Ci,
 ) -> Result<(), SaveError> {
-    let path = get_saved_objects_dir();
-    match path {
-        Some(mut path) => {
-            path.push(output.as_ref());
-            path.set_extension("json");
-            match std::fs::write(path.clone(), contents) {
-                Ok(()) => (),
-                Err(error) => return Err(SaveError::CouldntWriteObject { path, error }),
-            }
-            path.set_extension("png");
-            match std::fs::write(path.clone(), image) {
-                Ok(()) => (),
-                Err(error) => return Err(SaveError::CouldntWriteImage { path, error }),
-            }
-        }
-        None => return Err(SaveError::CouldntFindSaveDirectory),
+    let Some((json_path, image_path)) = resolve_tts_output_paths(output) else {
+        return Err(SaveError::CouldntFindSaveDirectory);
+    };
+    create_parent_dir(&json_path)?;
+    write_to_path(json_path, image_path, contents, image)
+}
+
+/// The paths `write_to_tts_dir` writes the object json and the image to, without writing anything. Useful to tell users where the files will be. `None` if the default TTS save directory can't be found.
+#[cfg(feature = "fs")]
+#[must_use]
+pub fn resolve_tts_output_paths<P: AsRef<Path>>(output: P) -> Option<(PathBuf, PathBuf)> {
+    let path = get_saved_objects_dir()?.join(output);
+    Some((path.with_extension("json"), path.with_extension("png")))
+}
+
+/// Writes only the object to the default TTS save directory, creating it if it doesn't exist yet. Useful when the cards use remote URLs, since Tabletop Simulator doesn't need a local image for them.
+/// # Errors
+/// - If the object json file can't be written
+/// - If the default TTS save directory can't be found or created
+#[cfg(feature = "fs")]
+pub fn write_json_to_tts_dir<P: AsRef<Path>, Cc: AsRef<[u8]>>(
+    output: P,
+    contents: Cc,
+) -> Result<(), SaveError> {
+    let Some((json_path, _)) = resolve_tts_output_paths(output) else {
+        return Err(SaveError::CouldntFindSaveDirectory);
+    };
+    create_parent_dir(&json_path)?;
+    write_json_to_path(json_path, contents)
+}
+
+/// Creates the directory `path` is in, along with any missing parents.
+#[cfg(feature = "fs")]
+fn create_parent_dir(path: &Path) -> Result<(), SaveError> {
+    let Some(parent) = path.parent() else {
+        return Ok(());
+    };
+    std::fs::create_dir_all(parent).map_err(|error| SaveError::CouldntCreateSaveDirectory {
+        path: parent.to_path_buf(),
+        error,
+    })
+}
+
+/// Writes the object and its image to the exact paths given, without looking for the TTS save directory or changing their extensions.
+/// # Errors
+/// - If the object json file can't be written
+/// - If the object image file can't be written
+#[cfg(feature = "fs")]
+pub fn write_to_path<Pj: AsRef<Path>, Pi: AsRef<Path>, Cc: AsRef<[u8]>, Ci: AsRef<[u8]>>(
+    json_path: Pj,
+    image_path: Pi,
+    contents: Cc,
+    image: Ci,
+) -> Result<(), SaveError> {
+    write_json_to_path(json_path, contents)?;
+    let image_path = image_path.as_ref();
+    if let Err(error) = std::fs::write(image_path, image) {
+        return Err(SaveError::CouldntWriteImage {
+            path: image_path.to_path_buf(),
+            error,
+        });
     }
     Ok(())
 }
 
+/// Writes only the object to the exact path given, without looking for the TTS save directory or changing its extension.
+/// # Errors
+/// If the object json file can't be written
+#[cfg(feature = "fs")]
+pub fn write_json_to_path<P: AsRef<Path>, Cc: AsRef<[u8]>>(
+    json_path: P,
+    contents: Cc,
+) -> Result<(), SaveError> {
+    let json_path = json_path.as_ref();
+    std::fs::write(json_path, contents).map_err(|error| SaveError::CouldntWriteObject {
+        path: json_path.to_path_buf(),
+        error,
+    })
+}
+
 /// Gets the default saved objects directory for Tabletop Simulator. Implemented for Windows, Mac OS and Linux. The output value of this function is different depending on what OS it's been compiled for.
-#[cfg(target_os = "windows")]
+#[cfg(feature = "fs")]
 #[must_use]
 pub fn get_saved_objects_dir() -> Option<PathBuf> {
-    let mut dir = dirs::home_dir();
-    if let Some(dir) = dir.as_mut() {
-        dir.push("Documents\\My Games\\Tabletop Simulator\\Saves\\Saved Objects");
-    }
-    dir
+    get_tts_dir().map(|dir| dir.join("Saves").join("Saved Objects"))
 }
 
-/// Gets the default saved objects directory for Tabletop Simulator. Implemented for Windows, Mac OS and Linux. The output value of this function is different depending on what OS it's been compiled for.
-#[cfg(target_os = "macos")]
+/// Gets the default mods directory for Tabletop Simulator. Implemented for Windows, Mac OS and Linux. The output value of this function is different depending on what OS it's been compiled for.
+#[cfg(feature = "fs")]
 #[must_use]
-pub fn get_tts_dir() -> Option<PathBuf> {
-    let mut dir = dirs::home_dir();
-    if let Some(dir) = dir.as_mut() {
-        dir.push("Library/Tabletop Simulator/Saves/Saved Objects");
-    }
-    dir
+pub fn get_mods_dir() -> Option<PathBuf> {
+    get_tts_dir().map(|dir| dir.join("Mods"))
 }
 
-/// Gets the default saved objects directory for Tabletop Simulator. Implemented for Windows, Mac OS and Linux. The output value of this function is different depending on what OS it's been compiled for.
-#[cfg(target_os = "linux")]
+/// Gets the default directory for Steam Workshop mods in Tabletop Simulator. Implemented for Windows, Mac OS and Linux. The output value of this function is different depending on what OS it's been compiled for.
+#[cfg(feature = "fs")]
+#[must_use]
+pub fn get_workshop_dir() -> Option<PathBuf> {
+    get_tts_dir().map(|dir| dir.join("Mods").join("Workshop"))
+}
+
+/// Gets the saved objects directory for Tabletop Simulator within the given home directory, without checking whether it exists. Useful for testing, or for finding the directory of another user. The output value of this function is different depending on what OS it's been compiled for.
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
 #[must_use]
-pub fn get_tts_dir() -> Option<PathBuf> {
-    let mut dir = dirs::home_dir();
-    if let Some(dir) = dir.as_mut() {
-        dir.push(".local/share/Tabletop Simulator/Saves/Saved Objects");
+pub fn saved_objects_dir_from_home(home: &Path) -> PathBuf {
+    tts_dir_from_home(home).join("Saves").join("Saved Objects")
+}
+
+/// Gets the directory where Tabletop Simulator keeps saves and mods.
+#[cfg(all(
+    feature = "fs",
+    any(target_os = "windows", target_os = "macos", target_os = "linux")
+))]
+fn get_tts_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| tts_dir_from_home(&home))
+}
+
+/// Gets the directory where Tabletop Simulator keeps saves and mods. Tabletop Simulator doesn't run on this OS, so there is no directory to find.
+#[cfg(all(
+    feature = "fs",
+    not(any(target_os = "windows", target_os = "macos", target_os = "linux"))
+))]
+const fn get_tts_dir() -> Option<PathBuf> {
+    None
+}
+
+/// Gets the directory where Tabletop Simulator keeps saves and mods within the given home directory.
+#[cfg(target_os = "windows")]
+fn tts_dir_from_home(home: &Path) -> PathBuf {
+    home.join("Documents")
+        .join("My Games")
+        .join("Tabletop Simulator")
+}
+
+/// Gets the directory where Tabletop Simulator keeps saves and mods within the given home directory.
+#[cfg(target_os = "macos")]
+fn tts_dir_from_home(home: &Path) -> PathBuf {
+    home.join("Library").join("Tabletop Simulator")
+}
+
+/// Gets the directory where Tabletop Simulator keeps saves and mods within the given home directory.
+#[cfg(target_os = "linux")]
+fn tts_dir_from_home(home: &Path) -> PathBuf {
+    home.join(".local").join("share").join("Tabletop Simulator")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::test_card::{deck, CustomCard, ResolvingCard, TestCard};
+
+    thread_local! {
+        static FRONT_IMAGE_CALLS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    /// A card that counts how many times `get_front_image` was called on the current thread.
+    #[derive(Clone)]
+    struct CountingCard {
+        name: String,
+    }
+
+    impl GetCardInfo for CountingCard {
+        fn get_name(&self) -> &str {
+            &self.name
+        }
+
+        fn get_front_image(&self) -> Result<String, CardError> {
+            FRONT_IMAGE_CALLS.set(FRONT_IMAGE_CALLS.get() + 1);
+            Ok(format!("https://example.com/{}.png", self.name))
+        }
+
+        fn parse(string: &str) -> Result<Self, crate::parser::ParseError> {
+            Ok(Self {
+                name: string.to_owned(),
+            })
+        }
+    }
+
+    #[test]
+    fn front_images_are_asked_for_once_per_distinct_card() {
+        let cards = ["Witch", "Mechanic"]
+            .map(|name| CardEntry::try_new(CountingCard::parse(name).unwrap(), 40).unwrap());
+        SaveState::new_with_deck(cards.to_vec()).unwrap();
+        assert_eq!(FRONT_IMAGE_CALLS.get(), 2);
+    }
+
+    /// A deck of `cards` distinct cards with two copies each.
+    fn distinct_cards(cards: usize) -> Vec<CardEntry<TestCard>> {
+        let names: Vec<String> = (0..cards).map(|idx| format!("Card {idx}")).collect();
+        let names: Vec<(&str, u32)> = names.iter().map(|name| (name.as_str(), 2)).collect();
+        deck(&names)
+    }
+
+    #[test]
+    fn atlases_are_split_into_full_sheets() {
+        let deck = distinct_cards(75);
+        let layout = atlas_layout(&deck);
+        let dimensions: Vec<_> = layout
+            .iter()
+            .map(|sheet| (sheet.num_width, sheet.num_height, sheet.cards.len()))
+            .collect();
+        assert_eq!(dimensions, vec![(10, 7, 70), (5, 1, 5)]);
+        assert_eq!(layout[1].cards[0], "Card 70");
+        assert_eq!(atlas_layout(&distinct_cards(13))[0].num_height, 2);
+    }
+
+    #[test]
+    fn atlas_card_ids_count_within_each_sheet() {
+        let faces = ["https://example.com/0.png", "https://example.com/1.png"].map(String::from);
+        let (save, layout) = SaveState::new_with_atlas(&distinct_cards(75), &faces, None).unwrap();
+        assert_eq!(layout.len(), 2);
+        let deck_ids = save.object_states[0].deck_ids.as_ref().unwrap();
+        assert_eq!(deck_ids.len(), 150);
+        assert_eq!(deck_ids[..4], [100, 100, 101, 101]);
+        assert_eq!(deck_ids[138..142], [169, 169, 200, 200]);
+        assert_eq!(save.object_states[0].custom_deck[&2].face_url, faces[1]);
+        assert!(SaveState::new_with_atlas(&distinct_cards(75), &faces[..1], None).is_err());
+    }
+
+    #[test]
+    fn unique_backs_are_marked_on_their_sheet() {
+        let unique = CustomCard {
+            unique_back: true,
+            ..CustomCard::new("Transform")
+        };
+        let state = unique.clone().entry(1).get_custom_deck_state().unwrap();
+        assert!(state.unique_back);
+        assert_eq!(state.back_url, "https://example.com/Transform-back.png");
+        let deck = [CustomCard::new("Plain").entry(1), unique.entry(1)];
+        let faces = ["https://example.com/faces.png".to_owned()];
+        assert!(SaveState::new_with_atlas(&deck, &faces, None).is_err());
+        let backs = ["https://example.com/backs.png".to_owned()];
+        let (save, layout) = SaveState::new_with_atlas(&deck, &faces, Some(&backs)).unwrap();
+        assert!(layout[0].unique_back);
+        let state = &save.object_states[0].custom_deck[&1];
+        assert!(state.unique_back);
+        assert_eq!(state.back_url, backs[0]);
+    }
+
+    fn json(save: &SaveState) -> serde_json::Value {
+        serde_json::to_value(save).unwrap()
+    }
+
+    #[test]
+    fn save_settings_are_serialized() {
+        let save = SaveState::new_with_deck(deck(&[("Witch", 1)]))
+            .unwrap()
+            .with_save_name("My Deck".to_owned())
+            .with_tags(vec!["Cards".to_owned()])
+            .with_gravity(0.25)
+            .with_play_area(0.75)
+            .with_table("Table_Poker".to_owned())
+            .with_sky("Sky_Forest".to_owned());
+        let json = json(&save);
+        assert!(serde_json::to_string(&save)
+            .unwrap()
+            .contains(r#""SaveName":"My Deck""#));
+        assert_eq!(json["Tags"], serde_json::json!(["Cards"]));
+        assert_eq!(json["Gravity"], 0.25);
+        assert_eq!(json["PlayArea"], 0.75);
+        assert_eq!(json["Table"], "Table_Poker");
+        assert_eq!(json["Sky"], "Sky_Forest");
+    }
+
+    #[test]
+    fn saves_have_a_version_and_a_game_mode() {
+        let value = json(&SaveState::from_object_states(vec![]));
+        assert_eq!(value["VersionNumber"], TTS_VERSION_NUMBER);
+        assert_eq!(value["GameMode"], DEFAULT_GAME_MODE);
+        assert!(!TTS_VERSION_NUMBER.is_empty() && !DEFAULT_GAME_MODE.is_empty());
+        let value = json(
+            &SaveState::from_object_states(vec![])
+                .with_version_number("v1".to_owned())
+                .with_game_mode("Witches".to_owned()),
+        );
+        assert_eq!(value["VersionNumber"], "v1");
+        assert_eq!(value["GameMode"], "Witches");
+    }
+
+    #[test]
+    fn saves_are_dated_when_they_are_made() {
+        let before = tts_date(SystemTime::now());
+        let date = SaveState::from_object_states(vec![]).date;
+        let after = tts_date(SystemTime::now());
+        assert!(date == before || date == after);
+    }
+
+    #[test]
+    fn the_date_is_written_verbatim() {
+        let date = "10/16/2026 9:30:00 AM".to_owned();
+        let save = SaveState::from_object_states(vec![]).with_date(date.clone());
+        assert_eq!(json(&save)["Date"], date);
+        let save = SaveState::from_object_states(vec![])
+            .with_date_at(UNIX_EPOCH + std::time::Duration::from_secs(1_735_830_245));
+        assert_eq!(json(&save)["Date"], "1/2/2025 3:04:05 PM");
+    }
+
+    #[test]
+    fn tts_date_matches_the_format_of_tabletop_simulator() {
+        let at = |seconds| tts_date(UNIX_EPOCH + std::time::Duration::from_secs(seconds));
+        assert_eq!(at(0), "1/1/1970 12:00:00 AM");
+        assert_eq!(at(1_735_830_245), "1/2/2025 3:04:05 PM");
+        assert_eq!(at(951_782_400 + 43_200), "2/29/2000 12:00:00 PM");
+        assert_eq!(
+            tts_date(UNIX_EPOCH - std::time::Duration::from_secs(1)),
+            at(0)
+        );
+    }
+
+    #[test]
+    fn objects_have_a_colour_diffuse() {
+        let save = SaveState::new_with_deck(deck(&[("Witch", 1)])).unwrap();
+        let deck = &json(&save)["ObjectStates"][0];
+        assert_eq!(deck["ColorDiffuse"]["r"], 0.713_235_259);
+        assert_eq!(
+            deck["ContainedObjects"][0]["ColorDiffuse"]["g"],
+            0.713_235_259
+        );
+        assert!(deck.get("ColorDifuse").is_none());
+    }
+
+    #[test]
+    fn json_round_trip_keeps_the_save() {
+        let save = SaveState::new_with_deck(deck(&[("Witch", 3), ("Mechanic", 1)])).unwrap();
+        let read = SaveState::from_json(&serde_json::to_string(&save).unwrap()).unwrap();
+        assert_eq!(json(&read), json(&save));
+    }
+
+    #[test]
+    fn to_json_parses_back_into_the_same_save() {
+        let save = SaveState::new_with_deck(deck(&[("Witch", 3), ("Mechanic", 1)])).unwrap();
+        for json_string in [save.to_json().unwrap(), save.to_json_pretty().unwrap()] {
+            let read = SaveState::from_json(&json_string).unwrap();
+            assert_eq!(json(&read), json(&save));
+        }
+        assert!(save.to_json_pretty().unwrap().contains('\n'));
+    }
+
+    #[test]
+    fn deck_face_urls_counts_the_cards_of_each_sheet() {
+        let save = SaveState::new_with_deck(deck(&[("Witch", 3), ("Mechanic", 1)])).unwrap();
+        let save = SaveState::from_json(&serde_json::to_string(&save).unwrap()).unwrap();
+        assert_eq!(
+            save.deck_face_urls().unwrap(),
+            vec![
+                ("https://example.com/Witch.png".to_owned(), 3),
+                ("https://example.com/Mechanic.png".to_owned(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn with_transform_only_moves_the_decks() {
+        let save = SaveState::new_with_decks(vec![
+            ("First".to_owned(), deck(&[("Witch", 1)])),
+            ("Second".to_owned(), deck(&[("Mechanic", 1)])),
+        ])
+        .unwrap();
+        let before: Vec<_> = save.object_states().iter().map(|o| o.transform).collect();
+        let origin = before[0];
+        let save = save.with_transform(TransformState::at(Vector3::new(10.0, 1.0, -4.0)));
+        for (before, object_state) in before.iter().zip(save.object_states()) {
+            let expected = TransformState {
+                pos_x: 10.0 + before.pos_x - origin.pos_x,
+                pos_y: 1.0,
+                pos_z: -4.0,
+                ..*before
+            };
+            assert_eq!(object_state.transform, expected);
+        }
+        assert!((save.object_states()[0].transform.rot_z - 180.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn deck_writer_reports_card_errors() {
+        let cards = deck(&[("Witch", 1), ("NoBack", 1)]);
+        let mut json = vec![];
+        let error = DeckWriter::new(&cards).write(&mut json).unwrap_err();
+        assert!(matches!(error, crate::Error::Card { .. }));
+        assert!(json.is_empty());
+    }
+
+    #[test]
+    fn new_is_an_empty_save() {
+        assert!(SaveState::new().object_states().is_empty());
+        assert!(SaveState::default().object_states().is_empty());
+    }
+
+    #[test]
+    fn shared_back_is_used_by_every_sheet() {
+        let back_url = "https://example.com/back.png".to_owned();
+        let save = SaveState::new_with_deck_and_shared_back(
+            deck(&[("Witch", 2), ("Mechanic", 1), ("NoBack", 1)]),
+            back_url.clone(),
+        )
+        .unwrap();
+        let custom_deck = &save.object_states()[0].custom_deck;
+        assert_eq!(custom_deck.len(), 3);
+        for custom_deck_state in custom_deck.values() {
+            assert_eq!(custom_deck_state.back_url, back_url);
+            assert!(!custom_deck_state.unique_back);
+        }
+        for card in save.object_states()[0]
+            .contained_objects()
+            .into_iter()
+            .flatten()
+        {
+            assert!(card
+                .custom_deck
+                .values()
+                .all(|state| state.back_url == back_url));
+        }
+    }
+
+    #[test]
+    fn deck_face_urls_errors_without_a_deck() {
+        let save = SaveState::new_with_deck(deck(&[("Witch", 1)])).unwrap();
+        let mut json = json(&save);
+        json["ObjectStates"] = serde_json::json!([]);
+        let save = SaveState::from_json(&json.to_string()).unwrap();
+        assert!(save.deck_face_urls().is_err());
+    }
+
+    #[test]
+    fn multiple_decks_are_separate_objects() {
+        let save = SaveState::new_with_decks(vec![
+            ("Witches".to_owned(), deck(&[("Witch", 2)])),
+            ("Mechanics".to_owned(), deck(&[("Mechanic", 1)])),
+            ("Both".to_owned(), deck(&[("Witch", 1), ("Mechanic", 1)])),
+        ])
+        .unwrap();
+        let object_states = &save.object_states;
+        assert_eq!(object_states.len(), 3);
+        let nicknames: Vec<_> = object_states.iter().map(|o| o.nickname.as_str()).collect();
+        assert_eq!(nicknames, vec!["Witches", "Mechanics", "Both"]);
+        let positions: Vec<_> = object_states.iter().map(|o| o.transform.pos_x).collect();
+        assert_eq!(positions, vec![0.0, DECK_SPACING, 2.0 * DECK_SPACING]);
+        let guids: std::collections::HashSet<_> =
+            object_states.iter().map(|o| o.guid.as_str()).collect();
+        assert_eq!(guids.len(), 3);
+    }
+
+    #[test]
+    fn with_transform_moves_every_deck() {
+        let save = SaveState::new_with_decks(vec![
+            ("Witches".to_owned(), deck(&[("Witch", 2)])),
+            ("Mechanics".to_owned(), deck(&[("Mechanic", 1)])),
+        ])
+        .unwrap()
+        .with_transform(TransformState {
+            pos_x: 10.0,
+            pos_z: -4.0,
+            ..TransformState::default()
+        });
+        let json = json(&save);
+        assert_eq!(json["ObjectStates"][0]["Transform"]["posX"], 10.0);
+        assert_eq!(json["ObjectStates"][0]["Transform"]["posZ"], -4.0);
+        assert_eq!(
+            json["ObjectStates"][1]["Transform"]["posX"],
+            10.0 + DECK_SPACING
+        );
+    }
+
+    #[test]
+    fn custom_objects_can_be_added_next_to_the_deck() {
+        let token = ObjectState::new_card(100, BTreeMap::new(), &mut || "token".to_owned())
+            .with_nickname("Token".to_owned())
+            .with_description("Not part of the deck".to_owned())
+            .with_lua_script("print('hi')".to_owned());
+        let save = SaveState::new_with_deck(deck(&[("Witch", 1)]))
+            .unwrap()
+            .with_object(token);
+        let json = json(&save);
+        assert_eq!(json["ObjectStates"][0]["Name"], "Deck");
+        let token = &json["ObjectStates"][1];
+        assert_eq!(token["Name"], "CardCustom");
+        assert_eq!(token["Nickname"], "Token");
+        assert_eq!(token["Description"], "Not part of the deck");
+        assert_eq!(token["LuaScript"], "print('hi')");
+        let deck =
+            ObjectState::new_deck(vec![], BTreeMap::new(), vec![], &mut || "deck".to_owned());
+        assert_eq!(deck.name, "Deck");
+        assert_eq!(deck.contained_objects, Some(vec![]));
+    }
+
+    /// Blanks out every GUID in `value`, since they're random.
+    fn without_guids(mut value: serde_json::Value) -> serde_json::Value {
+        match &mut value {
+            serde_json::Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    *value = if key == "Guid" {
+                        serde_json::Value::String(String::new())
+                    } else {
+                        without_guids(value.take())
+                    };
+                }
+            }
+            serde_json::Value::Array(values) => {
+                for value in values {
+                    *value = without_guids(value.take());
+                }
+            }
+            _ => (),
+        }
+        value
+    }
+
+    #[test]
+    fn a_deck_serializes_like_the_snapshot() {
+        let save = SaveState::new_with_deck(deck(&[("Witch", 2)]))
+            .unwrap()
+            .with_date_at(UNIX_EPOCH);
+        let snapshot: serde_json::Value =
+            serde_json::from_str(include_str!("snapshots/witch_deck.json")).unwrap();
+        assert_eq!(without_guids(json(&save)), snapshot);
+    }
+
+    /// The serialized cards of the first deck of `save`.
+    fn cards(save: &SaveState) -> Vec<serde_json::Value> {
+        json(save)["ObjectStates"][0]["ContainedObjects"]
+            .as_array()
+            .unwrap()
+            .clone()
+    }
+
+    #[test]
+    fn cards_have_their_name_and_description() {
+        let described = CustomCard {
+            description: Some("Flies on a broom".to_owned()),
+            ..CustomCard::new("Witch")
+        };
+        let save =
+            SaveState::new_with_deck(vec![described.entry(1), CustomCard::new("Cat").entry(1)])
+                .unwrap();
+        let cards = cards(&save);
+        assert_eq!(cards[0]["Nickname"], "Witch");
+        assert_eq!(cards[0]["Description"], "Flies on a broom");
+        assert_eq!(cards[1]["Nickname"], "Cat");
+        assert_eq!(cards[1]["Description"], "");
+    }
+
+    #[test]
+    fn only_sideways_cards_are_sideways() {
+        let sideways = CustomCard {
+            sideways: true,
+            ..CustomCard::new("Battlefield")
+        };
+        let save =
+            SaveState::new_with_deck(vec![sideways.entry(1), CustomCard::new("Cat").entry(1)])
+                .unwrap();
+        let sideways: Vec<_> = cards(&save)
+            .iter()
+            .map(|card| card["SidewaysCard"].clone())
+            .collect();
+        assert_eq!(sideways, vec![true, false]);
+    }
+
+    #[test]
+    fn lua_scripts_round_trip() {
+        let script = "function onLoad()\n    print(\"Hello, \\\"Witch\\\"\")\nend\n".to_owned();
+        let save = SaveState::new_with_deck(deck(&[("Witch", 1)]))
+            .unwrap()
+            .with_lua_script(script.clone())
+            .with_lua_script_state("{\"turn\": 1}".to_owned());
+        let json = save.to_json().unwrap();
+        assert!(!json.contains('\n'));
+        let read = SaveState::from_json(&json).unwrap();
+        assert_eq!(read.lua_script, script);
+        assert_eq!(read.lua_script_state, "{\"turn\": 1}");
+        let object = ObjectState::new_card(100, BTreeMap::new(), &mut || "card".to_owned())
+            .with_lua_script(script.clone())
+            .with_lua_script_state("state".to_owned());
+        let read: ObjectState =
+            serde_json::from_str(&serde_json::to_string(&object).unwrap()).unwrap();
+        assert_eq!(read.lua_script, script);
+        assert_eq!(read.lua_script_state, "state");
+    }
+
+    #[test]
+    fn hidden_backs_can_be_turned_off() {
+        let build = || SaveState::new_with_deck(deck(&[("Witch", 2)])).unwrap();
+        for (save, hidden) in [
+            (build(), true),
+            (build().with_hidden_backs(true), true),
+            (build().with_hidden_backs(false), false),
+        ] {
+            let deck = &json(&save)["ObjectStates"][0];
+            assert_eq!(deck["HideWhenFaceDown"], hidden);
+            assert_eq!(deck["CustomDeck"]["1"]["BackIsHidden"], hidden);
+            for card in cards(&save) {
+                assert_eq!(card["HideWhenFaceDown"], hidden);
+                assert_eq!(card["CustomDeck"]["1"]["BackIsHidden"], hidden);
+            }
+        }
+    }
+
+    #[test]
+    fn deck_writer_writes_the_same_save_as_new_with_deck() {
+        let cards = deck(&[("Witch", 3), ("Mechanic", 1)]);
+        let mut written = vec![];
+        DeckWriter::new(&cards).write(&mut written).unwrap();
+        let written = SaveState::from_json(std::str::from_utf8(&written).unwrap()).unwrap();
+        let built = SaveState::new_with_deck(cards.clone()).unwrap();
+        assert_eq!(
+            written.deck_face_urls().unwrap(),
+            built.deck_face_urls().unwrap()
+        );
+        let card_objects = card_objects(&cards).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(card_objects.len(), 4);
+        assert_eq!(
+            serde_json::to_value(&card_objects[3]).unwrap()["CardID"],
+            json(&built)["ObjectStates"][0]["ContainedObjects"][3]["CardID"]
+        );
+    }
+
+    #[test]
+    fn deck_writer_streams_big_decks() {
+        // The writer builds one card object at a time, so memory stays low however many copies there are
+        let cards = deck(&[
+            ("Witch", 1000),
+            ("Mechanic", 1000),
+            ("Knight", 1000),
+            ("Bard", 1000),
+            ("Thief", 1000),
+        ]);
+        let mut json = vec![];
+        DeckWriter::new(&cards).write(&mut json).unwrap();
+        let written = SaveState::from_json(std::str::from_utf8(&json).unwrap()).unwrap();
+        let deck = &written.object_states[0];
+        assert_eq!(deck.contained_objects.as_ref().unwrap().len(), 5000);
+    }
+
+    #[test]
+    fn card_shapes_from_integers() {
+        let shapes = [
+            CardShape::RoundedRectangle,
+            CardShape::Rectangle,
+            CardShape::RoundedHexagon,
+            CardShape::Hexagon,
+            CardShape::Circle,
+        ];
+        for (value, shape) in (0..).zip(shapes) {
+            assert_eq!(CardShape::from_i64(value), Some(shape));
+            assert_eq!(CardShape::try_from(value), Ok(shape));
+            assert_eq!(i64::from(shape), value);
+        }
+        for value in [-1, 5, i64::MAX] {
+            assert_eq!(CardShape::from_i64(value), None);
+            assert!(CardShape::try_from(value).is_err());
+        }
+    }
+
+    #[test]
+    fn card_shapes_from_names() {
+        for (names, shape) in [
+            (
+                ["Rounded Rectangle", "rounded_rectangle", "rounded-rect"],
+                CardShape::RoundedRectangle,
+            ),
+            (["Rectangle", "RECTANGLE", "rect"], CardShape::Rectangle),
+            (
+                ["Rounded Hexagon", "roundedhexagon", "rounded hex"],
+                CardShape::RoundedHexagon,
+            ),
+            (["Hexagon", "hexagon", "hex"], CardShape::Hexagon),
+            (["Circle", "circle", "CIRCLE"], CardShape::Circle),
+        ] {
+            for name in names {
+                assert_eq!(name.parse(), Ok(shape), "{name}");
+            }
+            assert_eq!(shape.to_string().parse(), Ok(shape));
+        }
+        let Err(error) = "triangle".parse::<CardShape>() else {
+            panic!("triangle isn't a card shape");
+        };
+        assert!(error.to_string().contains("triangle"));
+    }
+
+    #[test]
+    fn card_shapes_display_their_name() {
+        let names: Vec<_> = [
+            CardShape::RoundedRectangle,
+            CardShape::Rectangle,
+            CardShape::RoundedHexagon,
+            CardShape::Hexagon,
+            CardShape::Circle,
+        ]
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+        assert_eq!(
+            names,
+            vec![
+                "Rounded Rectangle",
+                "Rectangle",
+                "Rounded Hexagon",
+                "Hexagon",
+                "Circle"
+            ]
+        );
+    }
+
+    #[test]
+    fn custom_deck_states_read_the_shape_as_an_integer() {
+        let json = r#"{
+            "FaceUrl": "https://example.com/Witch.png",
+            "BackUrl": "https://example.com/back.png",
+            "NumWidth": 1,
+            "NumHeight": 1,
+            "BackIsHidden": true,
+            "UniqueBack": false,
+            "Type": 3
+        }"#;
+        let custom_deck_state: CustomDeckState = serde_json::from_str(json).unwrap();
+        assert_eq!(custom_deck_state.r#type, CardShape::Hexagon);
+        assert_eq!(serde_json::to_value(&custom_deck_state).unwrap()["Type"], 3);
+        let json = json.replace(r#""Type": 3"#, r#""Type": 9"#);
+        assert!(serde_json::from_str::<CustomDeckState>(&json).is_err());
+    }
+
+    #[test]
+    fn infinite_bags_hold_one_card() {
+        let save =
+            SaveState::new_with_infinite_bag("Tokens".to_owned(), TestCard::new("Witch")).unwrap();
+        let bag = &json(&save)["ObjectStates"][0];
+        assert_eq!(bag["Name"], "Infinite_Bag");
+        assert_eq!(bag["Nickname"], "Tokens");
+        assert_eq!(bag["MaterialIndex"], -1);
+        assert_eq!(bag["Bag"]["Order"], 0);
+        let contained = bag["ContainedObjects"].as_array().unwrap();
+        assert_eq!(contained.len(), 1);
+        assert_eq!(contained[0]["Name"], "CardCustom");
+        let card = &json(&SaveState::new_with_deck(deck(&[("Witch", 1)])).unwrap())["ObjectStates"]
+            [0]["ContainedObjects"][0];
+        assert!(card.get("MaterialIndex").is_none());
+        assert!(card.get("Bag").is_none());
+    }
+
+    #[test]
+    fn snap_points_are_only_written_when_there_are_some() {
+        let empty = SaveState::from_object_states(vec![]);
+        assert!(json(&empty).get("SnapPoints").is_none());
+        let snap_point = |x| SnapPoint {
+            position: Vector3::new(x, 1.0, 0.0),
+            ..SnapPoint::default()
+        };
+        let save = empty.with_snap_points(vec![snap_point(0.0), snap_point(2.5)]);
+        let snap_points = &json(&save)["SnapPoints"];
+        assert_eq!(snap_points.as_array().unwrap().len(), 2);
+        assert_eq!(snap_points[1]["Position"]["x"], 2.5);
+        assert_eq!(
+            SaveState::from_json(&save.to_json().unwrap()).unwrap(),
+            save
+        );
+    }
+
+    #[test]
+    fn unknown_keys_survive_a_round_trip() {
+        let mut value = json(&SaveState::new_with_deck(deck(&[("Witch", 1)])).unwrap());
+        value["Hands"] = serde_json::json!({ "Enable": true });
+        value["ObjectStates"][0]["AttachedDecals"] = serde_json::json!([]);
+        let save = SaveState::from_json(&value.to_string()).unwrap();
+        let written = json(&save);
+        assert_eq!(written["Hands"]["Enable"], true);
+        assert_eq!(
+            written["ObjectStates"][0]["AttachedDecals"],
+            serde_json::json!([])
+        );
+        assert_eq!(written, value);
+    }
+
+    #[test]
+    fn deck_guid_is_the_guid_in_the_json() {
+        let save = SaveState::new_with_deck(deck(&[("Witch", 1)])).unwrap();
+        let guid = save.deck_guid().unwrap();
+        assert_eq!(json(&save)["ObjectStates"][0]["Guid"], guid);
+        assert_eq!(SaveState::from_object_states(vec![]).deck_guid(), None);
+    }
+
+    #[test]
+    fn deterministic_guids_give_identical_saves() {
+        let namespace = Uuid::from_u128(0x5348_5245_4b44_4543_4b00_0000_0000_0001);
+        let build = |strategy: GuidStrategy| {
+            SaveState::new_with_deck_and_guids(
+                deck(&[("Witch", 3), ("Mechanic", 2)]),
+                strategy.generator(),
+            )
+            .unwrap()
+            .with_date_at(UNIX_EPOCH)
+        };
+        let deterministic = GuidStrategy::Deterministic { namespace };
+        let save = build(deterministic);
+        assert_eq!(
+            save.to_json().unwrap(),
+            build(deterministic).to_json().unwrap()
+        );
+        save.assert_unique_guids().unwrap();
+        let other_namespace = GuidStrategy::Deterministic {
+            namespace: Uuid::from_u128(2),
+        };
+        assert_ne!(save.deck_guid(), build(other_namespace).deck_guid());
+        let random = build(GuidStrategy::Random);
+        random.assert_unique_guids().unwrap();
+        assert_ne!(
+            random.to_json().unwrap(),
+            build(GuidStrategy::Random).to_json().unwrap()
+        );
+    }
+
+    #[test]
+    fn repeated_guids_are_found() {
+        let mut save = SaveState::new_with_deck(deck(&[("Witch", 2)])).unwrap();
+        save.assert_unique_guids().unwrap();
+        let cards = save.object_states[0].contained_objects.as_mut().unwrap();
+        "same".clone_into(&mut cards[0].guid);
+        "same".clone_into(&mut cards[1].guid);
+        let Err(error) = save.assert_unique_guids() else {
+            panic!("both cards have the same GUID");
+        };
+        assert!(error.to_string().contains("same"));
+        let mut save = SaveState::new_with_deck(deck(&[("Witch", 1)])).unwrap();
+        let deck_guid = save.object_states[0].guid.clone();
+        save.object_states[0].contained_objects.as_mut().unwrap()[0].guid = deck_guid;
+        assert!(save.assert_unique_guids().is_err());
+    }
+
+    #[test]
+    fn scale_applies_to_the_cards_and_the_deck() {
+        let mut tarot = CustomCard::new("Tarot");
+        tarot.scale = 1.5;
+        let save = SaveState::new_with_deck(vec![tarot.entry(2)]).unwrap();
+        let value = json(&save);
+        assert_eq!(value["ObjectStates"][0]["Transform"]["scaleX"], 1.5);
+        assert_eq!(value["ObjectStates"][0]["Transform"]["scaleZ"], 1.5);
+        for card in cards(&save) {
+            assert_eq!(card["Transform"]["scaleX"], 1.5);
+            assert_eq!(card["Transform"]["scaleY"], 1.5);
+        }
+    }
+
+    #[test]
+    fn transform_helpers_set_their_fields() {
+        assert_eq!(
+            Vector3::new(1.0, 2.0, 3.0),
+            Vector3 {
+                x: 1.0,
+                y: 2.0,
+                z: 3.0
+            }
+        );
+        let at = TransformState::at(Vector3::new(10.0, 1.0, -4.0));
+        assert_eq!(
+            at,
+            TransformState {
+                pos_x: 10.0,
+                pos_y: 1.0,
+                pos_z: -4.0,
+                ..TransformState::default()
+            }
+        );
+        assert_eq!(
+            at.with_rotation(Vector3::new(0.0, 90.0, 180.0)),
+            TransformState {
+                rot_y: 90.0,
+                rot_z: 180.0,
+                ..at
+            }
+        );
+        assert_eq!(
+            TransformState::face_down(),
+            TransformState {
+                rot_y: 180.0,
+                rot_z: 180.0,
+                ..TransformState::default()
+            }
+        );
+        let save = SaveState::new_with_deck(deck(&[("Witch", 1)])).unwrap();
+        assert_eq!(save.object_states[0].transform, TransformState::face_down());
+    }
+
+    #[test]
+    fn custom_decks_are_written_in_numeric_order() {
+        let names: Vec<_> = (1..=12).map(|idx| format!("Card {idx}")).collect();
+        let cards: Vec<_> = names.iter().map(|name| (name.as_str(), 1)).collect();
+        let namespace = Uuid::from_u128(7);
+        let build = || {
+            SaveState::new_with_deck_and_guids(
+                deck(&cards),
+                GuidStrategy::Deterministic { namespace }.generator(),
+            )
+            .unwrap()
+            .with_date_at(UNIX_EPOCH)
+            .to_json()
+            .unwrap()
+        };
+        let json = build();
+        assert_eq!(json, build());
+        let custom_deck = &json[json.find(r#""CustomDeck":{"#).unwrap()..];
+        let positions: Vec<_> = (1..=12)
+            .map(|idx| custom_deck.find(&format!(r#""{idx}":{{"#)).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn approx_eq_ignores_tiny_float_differences() {
+        let save = SaveState::new_with_deck(deck(&[("Witch", 1)])).unwrap();
+        let nudged = save
+            .clone()
+            .with_gravity(save.gravity + APPROX_EQ_EPSILON / 10.0);
+        assert_ne!(nudged, save);
+        assert!(nudged.approx_eq(&save));
+        assert!(!save
+            .clone()
+            .with_gravity(save.gravity + 0.1)
+            .approx_eq(&save));
+        assert!(!save
+            .clone()
+            .with_save_name("Witches".to_owned())
+            .approx_eq(&save));
+        let mut moved = save.clone();
+        moved.object_states[0].transform.pos_x += APPROX_EQ_EPSILON / 10.0;
+        assert!(moved.approx_eq(&save));
+        moved.object_states[0].transform.pos_x += 1.0;
+        assert!(!moved.approx_eq(&save));
+        assert!(save.approx_eq(&save.clone()));
+    }
+
+    #[test]
+    fn missing_back_policies() {
+        let cards = || deck(&[("Witch", 1), ("NoBack", 2)]);
+        let card_count = |save: &SaveState| {
+            save.object_states[0]
+                .contained_objects
+                .as_ref()
+                .unwrap()
+                .len()
+        };
+        assert!(
+            SaveState::new_with_deck_and_back_policy(cards(), &MissingImagePolicy::Error).is_err()
+        );
+        let default_back = "https://example.com/back.png".to_owned();
+        let (save, skipped) = SaveState::new_with_deck_and_back_policy(
+            cards(),
+            &MissingImagePolicy::UseDefault(default_back.clone()),
+        )
+        .unwrap();
+        assert!(matches!(
+            skipped.as_slice(),
+            [CardError::BackImageFileError { card_name, .. }] if card_name == "NoBack"
+        ));
+        let custom_deck = &save.object_states[0].custom_deck;
+        assert_eq!(custom_deck[&1].back_url, crate::PLACEHOLDER_BACK_IMAGE);
+        assert_eq!(custom_deck[&2].back_url, default_back);
+        assert_eq!(card_count(&save), 3);
+        let (save, skipped) =
+            SaveState::new_with_deck_and_back_policy(cards(), &MissingImagePolicy::Skip).unwrap();
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(card_count(&save), 1);
+        assert_eq!(save.object_states[0].custom_deck.len(), 1);
+    }
+
+    #[test]
+    fn removing_a_card_keeps_the_deck_consistent() {
+        let mut save = SaveState::new_with_deck(deck(&[("Witch", 2), ("Mechanic", 1)])).unwrap();
+        assert_eq!(save.object_states().len(), 1);
+        let deck = &mut save.object_states_mut()[0];
+        let guids: Vec<_> = deck
+            .contained_objects()
+            .into_iter()
+            .flatten()
+            .map(|card| card.guid().to_owned())
+            .collect();
+        assert!(deck.remove_card_by_guid(&guids[0]));
+        assert_eq!(deck.card_count(), 2);
+        assert_eq!(deck.deck_ids, Some(vec![100, 200]));
+        assert_eq!(deck.custom_deck.len(), 2);
+        assert!(deck.remove_card_by_guid(&guids[2]));
+        assert_eq!(deck.deck_ids, Some(vec![100]));
+        assert_eq!(deck.custom_deck.keys().collect::<Vec<_>>(), vec![&1]);
+        assert!(!deck.remove_card_by_guid(&guids[2]));
+        assert!(!deck.remove_card_by_guid("unknown"));
+        let card = &mut deck.contained_objects.as_mut().unwrap()[0];
+        assert_eq!(card.card_count(), 1);
+        assert!(!card.remove_card_by_guid(&guids[1]));
+    }
+
+    #[test]
+    fn with_deck_name_labels_the_deck() {
+        let save = SaveState::new_with_deck(deck(&[("Witch", 1)]))
+            .unwrap()
+            .with_deck_name("Witches".to_owned());
+        assert_eq!(save.object_states()[0].nickname, "Witches");
+        assert_eq!(json(&save)["ObjectStates"][0]["Nickname"], "Witches");
+        assert!(SaveState::from_object_states(vec![])
+            .with_deck_name("Nothing".to_owned())
+            .object_states()
+            .is_empty());
+    }
+
+    #[test]
+    fn orientation_sets_the_rotation_of_the_deck() {
+        let save = SaveState::new_with_deck(deck(&[("Witch", 1)])).unwrap();
+        let face_down = save.object_states()[0].transform;
+        assert_eq!(
+            face_down,
+            face_down.with_rotation(Vector3::new(0.0, 180.0, 180.0))
+        );
+        let face_up = save
+            .with_orientation(DeckOrientation::FaceUp)
+            .object_states()[0]
+            .transform;
+        assert_eq!(
+            face_up,
+            face_down.with_rotation(Vector3::new(0.0, 180.0, 0.0))
+        );
+        assert_eq!(DeckOrientation::default(), DeckOrientation::FaceDown);
+    }
+
+    #[test]
+    fn guids_come_from_the_given_generator() {
+        let mut count = 0;
+        let counter = || {
+            let guid = format!("guid-{count}");
+            count += 1;
+            guid
+        };
+        let save =
+            SaveState::new_with_deck_and_guids(deck(&[("Witch", 2), ("Mechanic", 1)]), counter)
+                .unwrap();
+        let cards: Vec<_> = save.object_states()[0]
+            .contained_objects()
+            .into_iter()
+            .flatten()
+            .map(ObjectState::guid)
+            .collect();
+        assert_eq!(cards, vec!["guid-0", "guid-1", "guid-2"]);
+        assert_eq!(save.deck_guid(), Some("guid-3"));
+    }
+
+    #[test]
+    fn object_constructors_use_the_given_generator() {
+        let mut guids = || "fixed".to_owned();
+        let card = ObjectState::new_card(100, BTreeMap::new(), &mut guids);
+        assert_eq!(card.guid(), "fixed");
+        let bag = ObjectState::new_infinite_bag(card, &mut guids);
+        assert_eq!(bag.guid(), "fixed");
+    }
+
+    #[test]
+    fn every_path_resolves_the_deck_once() {
+        let cards = ResolvingCard::deck(&["Witch", "Mechanic"]);
+        let save = SaveState::new_with_deck(cards.clone()).unwrap();
+        assert_eq!(ResolvingCard::resolve_calls(), 1);
+        assert_eq!(save.object_states()[0].card_count(), 4);
+        SaveState::new_with_deck_and_back_policy(cards.clone(), &MissingImagePolicy::Error)
+            .unwrap();
+        assert_eq!(ResolvingCard::resolve_calls(), 2);
+        let face_urls = vec!["https://example.com/sheet.png".to_owned()];
+        SaveState::new_with_atlas(&cards, &face_urls, None).unwrap();
+        assert_eq!(ResolvingCard::resolve_calls(), 3);
+        assert_eq!(card_objects(&cards).flatten().count(), 4);
+        assert_eq!(ResolvingCard::resolve_calls(), 4);
+        DeckWriter::new(&cards).write(io::sink()).unwrap();
+        assert_eq!(ResolvingCard::resolve_calls(), 5);
+    }
+
+    #[test]
+    fn card_ids_of_a_sheet_are_row_major() {
+        let ids: Vec<_> = [(0, 0), (0, 1), (1, 0), (1, 1)]
+            .into_iter()
+            .map(|(row, column)| card_id(1, row, column))
+            .collect();
+        assert_eq!(ids, vec![Some(100), Some(101), Some(110), Some(111)]);
+        assert_eq!(card_id(3, 6, 9), Some(369));
+        assert_eq!(card_id(1, 0, 10), None);
+        assert_eq!(card_id(1, 7, 0), None);
+        assert_eq!(card_id(i64::MAX / 100 + 1, 0, 0), None);
+        assert_eq!(checked_card_id(2, 13).unwrap(), 213);
+        assert!(checked_card_id(i64::MAX, 0).is_err());
+    }
+
+    #[test]
+    fn decks_over_the_limits_fail() {
+        let limits = DeckLimits {
+            max_copies_per_card: 3,
+            max_total_cards: 5,
+        };
+        assert!(SaveState::new_with_deck_and_limits(
+            deck(&[("Witch", 3), ("Mechanic", 2)]),
+            &limits
+        )
+        .is_ok());
+        assert!(SaveState::new_with_deck_and_limits(deck(&[("Witch", 4)]), &limits).is_err());
+        assert!(SaveState::new_with_deck_and_limits(
+            deck(&[("Witch", 3), ("Mechanic", 3)]),
+            &limits
+        )
+        .is_err());
+        let limits = DeckLimits {
+            max_copies_per_card: u32::MAX,
+            max_total_cards: u64::MAX,
+        };
+        assert!(SaveState::new_with_deck_and_limits(deck(&[("Witch", 2)]), &limits).is_ok());
+    }
+
+    #[test]
+    fn huge_amounts_fail_before_building_any_card() {
+        let cards = deck(&[("Witch", u32::MAX)]);
+        assert!(SaveState::new_with_deck(cards.clone()).is_err());
+        assert!(DeckWriter::new(&cards).write(io::sink()).is_err());
+        let cards = deck(&[("Witch", u32::MAX), ("Mechanic", u32::MAX)]);
+        let limits = DeckLimits {
+            max_copies_per_card: u32::MAX,
+            max_total_cards: 100,
+        };
+        assert!(SaveState::new_with_deck_and_limits(cards, &limits).is_err());
+    }
+
+    #[test]
+    fn save_errors_are_debug_and_keep_their_source() {
+        use std::error::Error as _;
+
+        let io_error = || io::Error::new(io::ErrorKind::PermissionDenied, "denied");
+        let path = PathBuf::from("deck.json");
+        let errors = [
+            SaveError::CouldntWriteObject {
+                path: path.clone(),
+                error: io_error(),
+            },
+            SaveError::CouldntWriteImage {
+                path: path.clone(),
+                error: io_error(),
+            },
+            SaveError::CouldntCreateSaveDirectory {
+                path,
+                error: io_error(),
+            },
+        ];
+        for error in &errors {
+            assert!(format!("{error:?}").contains("PermissionDenied"));
+            assert!(error.source().is_some());
+        }
+        let error = SaveError::CouldntFindSaveDirectory;
+        assert_eq!(format!("{error:?}"), "CouldntFindSaveDirectory");
+        assert!(error.source().is_none());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn tts_directories_on_linux() {
+        assert_eq!(
+            saved_objects_dir_from_home(Path::new("/home/alice")),
+            Path::new("/home/alice/.local/share/Tabletop Simulator/Saves/Saved Objects")
+        );
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn tts_directories_on_macos() {
+        assert_eq!(
+            saved_objects_dir_from_home(Path::new("/Users/alice")),
+            Path::new("/Users/alice/Library/Tabletop Simulator/Saves/Saved Objects")
+        );
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn tts_directories_on_windows() {
+        assert_eq!(
+            saved_objects_dir_from_home(Path::new(r"C:\Users\alice")),
+            Path::new(r"C:\Users\alice\Documents\My Games\Tabletop Simulator\Saves\Saved Objects")
+        );
+    }
+
+    #[cfg(feature = "fs")]
+    mod files {
+        use super::*;
+        use crate::test_card::temp_dir;
+
+        #[test]
+        fn write_to_path_writes_both_files() {
+            let dir = temp_dir("write_to_path");
+            let (json_path, image_path) = (dir.join("deck.json"), dir.join("deck.png"));
+            write_to_path(&json_path, &image_path, "{}", [1, 2, 3]).unwrap();
+            assert_eq!(std::fs::read_to_string(&json_path).unwrap(), "{}");
+            assert_eq!(std::fs::read(&image_path).unwrap(), [1, 2, 3]);
+            let missing = dir.join("missing").join("deck.json");
+            assert!(matches!(
+                write_to_path(&missing, &image_path, "{}", []),
+                Err(SaveError::CouldntWriteObject { path, .. }) if path == missing
+            ));
+        }
+
+        #[test]
+        fn writing_only_the_json_skips_the_image() {
+            let dir = temp_dir("write_json_to_path");
+            write_json_to_path(dir.join("deck.json"), "{}").unwrap();
+            let files: Vec<_> = std::fs::read_dir(&dir)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name())
+                .collect();
+            assert_eq!(files, ["deck.json"]);
+        }
+
+        #[cfg(target_os = "linux")]
+        #[test]
+        fn tts_directories_are_inside_the_home_directory() {
+            crate::test_card::with_temp_home("tts_directories", |home| {
+                let tts_dir = home.join(".local/share/Tabletop Simulator");
+                assert_eq!(
+                    get_saved_objects_dir(),
+                    Some(tts_dir.join("Saves/Saved Objects"))
+                );
+                assert_eq!(get_mods_dir(), Some(tts_dir.join("Mods")));
+                assert_eq!(get_workshop_dir(), Some(tts_dir.join("Mods/Workshop")));
+            });
+        }
+
+        #[cfg(target_os = "linux")]
+        #[test]
+        fn output_paths_are_in_the_saved_objects_directory() {
+            crate::test_card::with_temp_home("output_paths", |home| {
+                let saved_objects = saved_objects_dir_from_home(home);
+                assert_eq!(
+                    resolve_tts_output_paths("Witches"),
+                    Some((
+                        saved_objects.join("Witches.json"),
+                        saved_objects.join("Witches.png")
+                    ))
+                );
+                assert!(!saved_objects.exists());
+            });
+        }
+
+        #[cfg(target_os = "linux")]
+        #[test]
+        fn writing_creates_the_saved_objects_directory() {
+            crate::test_card::with_temp_home("write_to_tts_dir", |home| {
+                let saved_objects = saved_objects_dir_from_home(home);
+                write_to_tts_dir("Witches", "{}", b"png").unwrap();
+                assert_eq!(
+                    std::fs::read_to_string(saved_objects.join("Witches.json")).unwrap(),
+                    "{}"
+                );
+                assert_eq!(
+                    std::fs::read(saved_objects.join("Witches.png")).unwrap(),
+                    b"png"
+                );
+            });
+        }
+
+        #[cfg(target_os = "linux")]
+        #[test]
+        fn failing_to_create_the_directory_is_an_error() {
+            crate::test_card::with_temp_home("create_dir_error", |home| {
+                std::fs::write(home.join(".local"), "").unwrap();
+                let Err(error) = write_json_to_tts_dir("Witches", "{}") else {
+                    panic!(".local is a file");
+                };
+                assert!(matches!(
+                    error,
+                    SaveError::CouldntCreateSaveDirectory { .. }
+                ));
+            });
+        }
+    }
+
+    #[cfg(feature = "async")]
+    mod asynchronous {
+        use super::*;
+
+        #[derive(Clone)]
+        struct AsyncCard {
+            name: String,
+        }
+
+        impl GetCardInfoAsync for AsyncCard {
+            fn get_name(&self) -> &str {
+                &self.name
+            }
+
+            async fn get_front_image(&self) -> Result<String, CardError> {
+                tokio::task::yield_now().await;
+                if self.name == "Missing" {
+                    return Err(CardError::CardDoesntExist {
+                        card_name: self.name.clone(),
+                    });
+                }
+                Ok(format!("https://example.com/{}.png", self.name))
+            }
+
+            async fn get_back_image(&self) -> Result<String, CardError> {
+                tokio::task::yield_now().await;
+                Ok("https://example.com/back.png".to_owned())
+            }
+
+            async fn get_card_shape(&self) -> Result<CardShape, CardError> {
+                Ok(CardShape::Circle)
+            }
+        }
+
+        fn entry(name: &str, amount: i64) -> CardEntry<AsyncCard> {
+            CardEntry {
+                card: AsyncCard {
+                    name: name.to_owned(),
+                },
+                amount: u32::try_from(amount)
+                    .ok()
+                    .and_then(NonZeroU32::new)
+                    .unwrap(),
+            }
+        }
+
+        fn block_on<F: std::future::Future>(future: F) -> F::Output {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap()
+                .block_on(future)
+        }
+
+        #[test]
+        fn new_with_deck_async_awaits_every_card() {
+            let save = block_on(SaveState::new_with_deck_async(vec![
+                entry("Witch", 2),
+                entry("Mechanic", 1),
+            ]))
+            .unwrap();
+            assert_eq!(
+                save.deck_face_urls().unwrap(),
+                vec![
+                    ("https://example.com/Witch.png".to_owned(), 2),
+                    ("https://example.com/Mechanic.png".to_owned(), 1),
+                ]
+            );
+            let custom_deck = &save.object_states[0].custom_deck;
+            assert!(custom_deck
+                .values()
+                .all(|state| state.r#type == CardShape::Circle
+                    && state.back_url == "https://example.com/back.png"));
+            assert!(block_on(SaveState::new_with_deck_async(vec![entry("Missing", 1)])).is_err());
+        }
     }
-    dir
 }